@@ -26,8 +26,35 @@ mod complex;
 mod complex_trait;
 mod complex_messaging;
 
-use picture_language::picture;
+use picture_language::picture::{self, PictureOptions};
 
+const USAGE: &str = "usage: sicp [PROGRAM.scm] [--image IMAGE] [--export OUT.svg|OUT.png] [--edit TILE.sexp]";
+
+// What the command line asks for: the demo picture or a DSL program, shown in a window or
+// exported, or else a tile to edit
+#[derive(Debug, PartialEq, Default)]
+struct Args {
+    picture: PictureOptions,
+    export: Option<String>,
+    edit: Option<String>,
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a file name", name));
+        match arg.as_str() {
+            "--image" => parsed.picture.image = Some(value("--image")?),
+            "--export" => parsed.export = Some(value("--export")?),
+            "--edit" => parsed.edit = Some(value("--edit")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if parsed.picture.program.is_none() => parsed.picture.program = Some(arg),
+            _ => return Err(format!("unexpected {}", arg)),
+        }
+    }
+    Ok(parsed)
+}
 
 // Following is from https://stackoverflow.com/questions/62960584/do-mutable-references-have-move-semantics
 fn main() {
@@ -38,7 +65,45 @@ fn main() {
     // say_hello(y);       // but y has not been moved, it is still usable
     // change_string(y); // Same as change_string(&mut *y); through compiler implicit re-borrowing
     // change_string(y);
-    picture::run_picture();
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Some(path) = &args.edit {
+        return edit(path);
+    }
+    let exported = match &args.export {
+        Some(path) => picture::export_picture(path, &args.picture),
+        None => show(&args.picture),
+    };
+    if let Err(error) = exported {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "window")]
+fn show(options: &PictureOptions) -> std::io::Result<()> {
+    picture::run_picture(options)
+}
+
+// Without a window the picture is exported instead
+#[cfg(not(feature = "window"))]
+fn show(options: &PictureOptions) -> std::io::Result<()> {
+    picture::export_picture("picture.svg", options)
+}
+
+#[cfg(feature = "window")]
+fn edit(path: &str) {
+    picture::edit_tile(path);
+}
+
+#[cfg(not(feature = "window"))]
+fn edit(path: &str) {
+    eprintln!("editing {} needs the window feature", path);
 }
 
 fn say_hello(s: &str) {
@@ -51,9 +116,28 @@ fn change_string(s: &mut String) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     // So we can run all the tests at once
     #[test]
     fn t() {
         assert_eq!(true, true);
     }
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn command_line() {
+        assert_eq!(args(&[]), Ok(Args::default()));
+        let parsed = args(&["limit.scm", "--export", "limit.png"]).unwrap();
+        assert_eq!(parsed.picture.program.as_deref(), Some("limit.scm"));
+        assert_eq!(parsed.export.as_deref(), Some("limit.png"));
+        assert_eq!(args(&["--image", "rogers.png"]).unwrap().picture.image.as_deref(), Some("rogers.png"));
+        assert_eq!(args(&["--edit", "tiles/boat.sexp"]).unwrap().edit.as_deref(), Some("tiles/boat.sexp"));
+        assert_eq!(args(&["--export"]), Err("--export needs a file name".to_string()));
+        assert_eq!(args(&["--fit"]), Err("unknown option --fit".to_string()));
+        assert_eq!(args(&["a.scm", "b.scm"]), Err("unexpected b.scm".to_string()));
+    }
 }
\ No newline at end of file
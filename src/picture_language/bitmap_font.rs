@@ -0,0 +1,202 @@
+use crate::picture_language::renderer::Renderer;
+use crate::picture_language::shape::Anchor;
+use crate::picture_language::style::Style;
+use crate::picture_language::vector::Vector;
+
+// A 5 x 7 pixel font for printable ASCII, one byte per column with the top row in the lowest bit.
+// Each character takes a 6 x 8 cell, which leaves a gap between characters and below descenders.
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x56, 0x20, 0x50], // &
+    [0x00, 0x00, 0x07, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x14, 0x08, 0x3E, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+const CELL_WIDTH: f32 = 6.0;
+const CELL_HEIGHT: f32 = 8.0;
+
+// The box taken by a line of text `size` high
+pub fn text_size(text: &str, size: f32) -> Vector {
+    Vector::new(text.chars().count() as f32 * CELL_WIDTH * size / CELL_HEIGHT, size)
+}
+
+// Each column of each glyph as filled rectangles, so any renderer can draw it.
+// Characters outside printable ASCII are drawn as '?'.
+pub fn draw_text<R: Renderer + ?Sized>(renderer: &mut R, text: &str, position: &Vector, size: f32,
+                                       anchor: Anchor, style: &Style) {
+    let Some(colour) = style.text_colour() else { return };
+    let fill = Style::fill(colour);
+    let pixel = size / CELL_HEIGHT;
+    let top_left = anchor.top_left(position, &text_size(text, size));
+    for (i, c) in text.chars().enumerate() {
+        for (column, bits) in glyph(c).iter().enumerate() {
+            let x = top_left.x + (i as f32 * CELL_WIDTH + column as f32) * pixel;
+            runs(*bits).iter().for_each(|(start, end)|
+                renderer.draw_rect(&Vector::new(x, top_left.y + *start as f32 * pixel),
+                                   &Vector::new(x + pixel, top_left.y + *end as f32 * pixel), &fill));
+        }
+    }
+}
+
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        ' '..='~' => GLYPHS[c as usize - ' ' as usize],
+        _ => GLYPHS['?' as usize - ' ' as usize],
+    }
+}
+
+// The rows covered by each run of set bits, from start up to but not including end
+fn runs(bits: u8) -> Vec<(u32, u32)> {
+    let mut runs = vec![];
+    let mut row = 0;
+    while row < 8 {
+        if bits & (1 << row) == 0 {
+            row += 1;
+        } else {
+            let start = row;
+            while row < 8 && bits & (1 << row) != 0 {
+                row += 1;
+            }
+            runs.push((start, row));
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+    use crate::picture_language::renderer::{DrawCall, RecordingRenderer};
+
+    #[test]
+    fn runs() {
+        assert_eq!(super::runs(0x00), vec![]);
+        assert_eq!(super::runs(0x7F), vec![(0, 7)]);
+        assert_eq!(super::runs(0x49), vec![(0, 1), (3, 4), (6, 7)]);
+        assert_eq!(super::runs(0x80), vec![(7, 8)]);
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(glyph('A'), [0x7E, 0x11, 0x11, 0x11, 0x7E]);
+        assert_eq!(glyph('~'), GLYPHS[94]);
+        assert_eq!(glyph('é'), glyph('?'));
+    }
+
+    #[test]
+    fn text_size() {
+        assert_eq!(super::text_size("abc", 16.0), Vector::new(36.0, 16.0));
+    }
+
+    #[test]
+    fn draw_text() {
+        let mut renderer = RecordingRenderer::new();
+        super::draw_text(&mut renderer, "|", &Vector::new(10.0, 20.0), 16.0, Anchor::TopLeft,
+                         &Style::fill(Colour::RED));
+        assert_eq!(renderer.calls, vec![
+            DrawCall::Rect(Vector::new(14.0, 20.0), Vector::new(16.0, 34.0), Style::fill(Colour::RED)),
+        ]);
+    }
+
+    #[test]
+    fn anchored_text() {
+        let mut renderer = RecordingRenderer::new();
+        super::draw_text(&mut renderer, "|", &Vector::new(10.0, 20.0), 16.0, Anchor::Centre,
+                         &Style::fill(Colour::RED));
+        assert_eq!(renderer.calls, vec![
+            DrawCall::Rect(Vector::new(8.0, 12.0), Vector::new(10.0, 26.0), Style::fill(Colour::RED)),
+        ]);
+    }
+}
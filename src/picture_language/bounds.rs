@@ -0,0 +1,98 @@
+use crate::picture_language::vector::Vector;
+
+// An axis-aligned rectangle, from its least to its greatest corner
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Bounds {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Bounds {
+    pub fn new(min: Vector, max: Vector) -> Self {
+        Bounds { min, max }
+    }
+
+    // The smallest bounds holding all the points, or None if there are none
+    pub fn from_points(points: &[Vector]) -> Option<Self> {
+        let first = points.first()?;
+        Some(points.iter().fold(Bounds::new(*first, *first), |bounds, p| bounds.including(*p)))
+    }
+
+    pub fn including(&self, p: Vector) -> Self {
+        Bounds::new(Vector::new(self.min.x.min(p.x), self.min.y.min(p.y)),
+                    Vector::new(self.max.x.max(p.x), self.max.y.max(p.y)))
+    }
+
+    pub fn union(&self, other: &Bounds) -> Self {
+        self.including(other.min).including(other.max)
+    }
+
+    // The union of all of them, or None if there are none
+    pub fn union_all<I: IntoIterator<Item = Bounds>>(bounds: I) -> Option<Self> {
+        bounds.into_iter().reduce(|b1, b2| b1.union(&b2))
+    }
+
+    // Grown by margin on every side
+    pub fn expand(&self, margin: f32) -> Self {
+        let margin = Vector::new(margin, margin);
+        Bounds::new(self.min - margin, self.max + margin)
+    }
+
+    pub fn translate(&self, v: Vector) -> Self {
+        Bounds::new(self.min + v, self.max + v)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn centre(&self) -> Vector {
+        (self.min + self.max).scale(0.5)
+    }
+
+    pub fn contains(&self, p: Vector) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points() {
+        let bounds = Bounds::from_points(&[Vector::new(1.0, -2.0), Vector::new(-3.0, 4.0), Vector::new(0.0, 0.0)]);
+        assert_eq!(bounds, Some(Bounds::new(Vector::new(-3.0, -2.0), Vector::new(1.0, 4.0))));
+        assert_eq!(Bounds::from_points(&[]), None);
+    }
+
+    #[test]
+    fn measurements() {
+        let bounds = Bounds::new(Vector::new(-3.0, -2.0), Vector::new(1.0, 4.0));
+        assert_eq!((bounds.width(), bounds.height()), (4.0, 6.0));
+        assert_eq!(bounds.centre(), Vector::new(-1.0, 1.0));
+        assert!(bounds.contains(Vector::new(1.0, 0.0)));
+        assert!(!bounds.contains(Vector::new(1.5, 0.0)));
+        assert_eq!(bounds.expand(1.0), Bounds::new(Vector::new(-4.0, -3.0), Vector::new(2.0, 5.0)));
+        assert_eq!(bounds.translate(Vector::one()), Bounds::new(Vector::new(-2.0, -1.0), Vector::new(2.0, 5.0)));
+    }
+
+    #[test]
+    fn union() {
+        let b1 = Bounds::new(Vector::zero(), Vector::one());
+        let b2 = Bounds::new(Vector::new(2.0, -1.0), Vector::new(3.0, 0.5));
+        assert_eq!(b1.union(&b2), Bounds::new(Vector::new(0.0, -1.0), Vector::new(3.0, 1.0)));
+        assert_eq!(Bounds::union_all([b1, b2]), Some(b1.union(&b2)));
+        assert_eq!(Bounds::union_all([]), None);
+        assert!(!b1.intersects(&b2));
+        assert!(b1.intersects(&b1.translate(Vector::new(0.5, 0.5))));
+    }
+}
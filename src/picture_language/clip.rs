@@ -0,0 +1,206 @@
+use crate::picture_language::curve::{self, Curve};
+use crate::picture_language::frame::Frame;
+use crate::picture_language::segment::Segment;
+use crate::picture_language::shape::{ellipse_points, Shape};
+use crate::picture_language::style::Style;
+use crate::picture_language::vector::Vector;
+
+// Points this close to the frame's edge, in its unit square, count as inside
+const EPSILON: f32 = 1e-5;
+
+// The parts of the shape inside the frame's parallelogram. Shapes that are already inside
+// are returned unchanged. Otherwise fills are clipped as polygons and outlines as polylines,
+// which may come apart into several pieces. Text is kept only when its position is inside,
+// and images are always kept, as they fill their own frame.
+pub fn clip_shape(shape: &Shape, frame: &Frame) -> Vec<Shape> {
+    if frame.scale() == 0.0 {
+        return vec![];
+    }
+    if hull(shape).iter().all(|p| inside(frame, *p)) {
+        return vec![shape.clone()];
+    }
+    match shape {
+        Shape::Line(segment, style) =>
+            segment.clip_to_frame(frame).map(|s| Shape::new_line(s, style.clone())).into_iter().collect(),
+        Shape::PolyLine(points, closed, style) =>
+            outline(points, *closed, style, frame),
+        Shape::Path(curves, closed, style) =>
+            area(&curve::flatten_path(curves, curve::TOLERANCE), *closed, style, frame),
+        Shape::Circle(centre, radius, style) =>
+            area(&ellipse_points(centre, Vector::new(*radius, 0.0), Vector::new(0.0, *radius)), true, style, frame),
+        Shape::Ellipse(centre, axis1, axis2, style) =>
+            area(&ellipse_points(centre, *axis1, *axis2), true, style, frame),
+        Shape::Polygon(points, style) =>
+            area(points, true, style, frame),
+        Shape::Rect(min, max, style) =>
+            area(&[*min, Vector::new(max.x, min.y), *max, Vector::new(min.x, max.y)], true, style, frame),
+        Shape::Text(..) => vec![],
+        Shape::Image(..) => vec![shape.clone()],
+    }
+}
+
+// Points whose convex hull contains the shape
+fn hull(shape: &Shape) -> Vec<Vector> {
+    let corners = |centre: &Vector, axis1: Vector, axis2: Vector| vec![
+        *centre - axis1 - axis2, *centre + axis1 - axis2, *centre + axis1 + axis2, *centre - axis1 + axis2,
+    ];
+    match shape {
+        Shape::Line(segment, _) => vec![segment.start, segment.end],
+        Shape::PolyLine(points, _, _) | Shape::Polygon(points, _) => points.clone(),
+        Shape::Path(curves, _, _) => curves.iter().flat_map(|curve| match curve {
+            Curve::Line(start, end) => vec![*start, *end],
+            Curve::Quadratic(p0, p1, p2) => vec![*p0, *p1, *p2],
+            Curve::Cubic(p0, p1, p2, p3) => vec![*p0, *p1, *p2, *p3],
+            Curve::Arc(centre, axis1, axis2, _, _) => corners(centre, *axis1, *axis2),
+        }).collect(),
+        Shape::Circle(centre, radius, _) => corners(centre, Vector::new(*radius, 0.0), Vector::new(0.0, *radius)),
+        Shape::Ellipse(centre, axis1, axis2, _) => corners(centre, *axis1, *axis2),
+        Shape::Rect(min, max, _) => vec![*min, *max, Vector::new(max.x, min.y), Vector::new(min.x, max.y)],
+        Shape::Text(_, position, _, _, _) => vec![*position],
+        Shape::Image(..) => vec![],
+    }
+}
+
+fn inside(frame: &Frame, p: Vector) -> bool {
+    frame.unmap(p).is_some_and(|unit|
+        (-EPSILON..=1.0 + EPSILON).contains(&unit.x) && (-EPSILON..=1.0 + EPSILON).contains(&unit.y))
+}
+
+fn area(points: &[Vector], closed: bool, style: &Style, frame: &Frame) -> Vec<Shape> {
+    let mut shapes = vec![];
+    if style.fill.is_some() {
+        let clipped = clip_polygon(points, frame);
+        if clipped.len() >= 3 {
+            shapes.push(Shape::new_polygon(clipped, Style { stroke: None, ..style.clone() }));
+        }
+    }
+    if style.stroke.is_some() {
+        shapes.extend(outline(points, closed, &Style { fill: None, ..style.clone() }, frame));
+    }
+    shapes
+}
+
+fn outline(points: &[Vector], closed: bool, style: &Style, frame: &Frame) -> Vec<Shape> {
+    clip_polyline(points, closed, frame).into_iter()
+        .map(|piece| Shape::new_poly_line(piece, false, style.clone()))
+        .collect()
+}
+
+// The pieces of the path inside the frame, each as an open polyline. A flat frame has no inside.
+pub fn clip_polyline(points: &[Vector], closed: bool, frame: &Frame) -> Vec<Vec<Vector>> {
+    let Some(unit) = points.iter().map(|p| frame.unmap(*p)).collect::<Option<Vec<Vector>>>() else { return vec![] };
+    let closing = if closed { unit.first().map(|first| (*unit.last().unwrap(), *first)) } else { None };
+    let segments = unit.iter().zip(unit.iter().skip(1)).map(|(start, end)| (*start, *end)).chain(closing);
+    let mut pieces: Vec<Vec<Vector>> = vec![];
+    for (start, end) in segments {
+        let Some(clipped) = Segment::new(start, end).clip_to_rect(Vector::zero(), Vector::one()) else { continue };
+        match pieces.last_mut() {
+            Some(piece) if (*piece.last().unwrap() - clipped.start).length() < EPSILON => piece.push(clipped.end),
+            _ => pieces.push(vec![clipped.start, clipped.end]),
+        }
+    }
+    // A closed path cut once runs on from its last piece into its first
+    if closed && pieces.len() > 1 && (*pieces.last().unwrap().last().unwrap() - pieces[0][0]).length() < EPSILON {
+        let last = pieces.pop().unwrap();
+        pieces[0] = [&last[..], &pieces[0][1..]].concat();
+    }
+    pieces.iter().map(|piece| piece.iter().map(|p| frame.map(*p)).collect()).collect()
+}
+
+// Sutherland-Hodgman against each side of the frame's unit square in turn, which works for
+// any polygon as the square is convex
+pub fn clip_polygon(points: &[Vector], frame: &Frame) -> Vec<Vector> {
+    let Some(mut polygon) = points.iter().map(|p| frame.unmap(*p)).collect::<Option<Vec<Vector>>>() else {
+        return vec![];
+    };
+    // Each side as the signed distance of a point inside it
+    let sides: [fn(Vector) -> f32; 4] = [|p| p.x, |p| 1.0 - p.x, |p| p.y, |p| 1.0 - p.y];
+    for side in sides {
+        let input = std::mem::take(&mut polygon);
+        for (i, current) in input.iter().enumerate() {
+            let previous = input[(i + input.len() - 1) % input.len()];
+            let (d0, d1) = (side(previous), side(*current));
+            if d0 * d1 < 0.0 {
+                polygon.push(previous + (*current - previous).scale(d0 / (d0 - d1)));
+            }
+            if d1 >= 0.0 {
+                polygon.push(*current);
+            }
+        }
+    }
+    polygon.iter().map(|p| frame.map(*p)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+    use crate::picture_language::shape::Anchor;
+
+    fn frame() -> Frame {
+        Frame::new(Vector::new(10.0, 10.0), Vector::new(10.0, 0.0), Vector::new(0.0, 10.0))
+    }
+
+    #[test]
+    fn shapes_inside_are_unchanged() {
+        let circle = Shape::new_circle(&Vector::new(15.0, 15.0), 5.0, Style::fill(Colour::RED));
+        assert_eq!(clip_shape(&circle, &frame()), vec![circle]);
+        let text = Shape::new_text("in", Vector::new(15.0, 15.0), 100.0, Anchor::Centre, Style::fill(Colour::RED));
+        assert_eq!(clip_shape(&text, &frame()), vec![text]);
+    }
+
+    #[test]
+    fn line() {
+        let style = Style::stroke(1.0, Colour::BLACK);
+        let line = Shape::new_line(Segment::new(Vector::new(0.0, 15.0), Vector::new(30.0, 15.0)), style.clone());
+        assert_eq!(clip_shape(&line, &frame()), vec![
+            Shape::new_line(Segment::new(Vector::new(10.0, 15.0), Vector::new(20.0, 15.0)), style.clone()),
+        ]);
+        let outside = Shape::new_line(Segment::new(Vector::new(0.0, 0.0), Vector::new(30.0, 0.0)), style);
+        assert_eq!(clip_shape(&outside, &frame()), vec![]);
+    }
+
+    #[test]
+    fn polyline_comes_apart() {
+        // In, out through the right side and back in again
+        let points = [Vector::new(12.0, 12.0), Vector::new(30.0, 12.0), Vector::new(30.0, 18.0), Vector::new(12.0, 18.0)];
+        assert_eq!(clip_polyline(&points, false, &frame()), vec![
+            vec![Vector::new(12.0, 12.0), Vector::new(20.0, 12.0)],
+            vec![Vector::new(20.0, 18.0), Vector::new(12.0, 18.0)],
+        ]);
+        // Closed, the last piece carries on into the first
+        assert_eq!(clip_polyline(&points, true, &frame()), vec![
+            vec![Vector::new(20.0, 18.0), Vector::new(12.0, 18.0), Vector::new(12.0, 12.0), Vector::new(20.0, 12.0)],
+        ]);
+    }
+
+    #[test]
+    fn polygon() {
+        let triangle = [Vector::new(15.0, 5.0), Vector::new(25.0, 15.0), Vector::new(15.0, 15.0)];
+        assert_eq!(clip_polygon(&triangle, &frame()), vec![
+            Vector::new(15.0, 10.0), Vector::new(20.0, 10.0), Vector::new(20.0, 15.0), Vector::new(15.0, 15.0),
+        ]);
+    }
+
+    #[test]
+    fn filled_and_outlined_circle() {
+        let style = Style::fill_and_stroke(Colour::RED, 1.0, Colour::BLACK);
+        let circle = Shape::new_circle(&Vector::new(20.0, 20.0), 5.0, style);
+        let clipped = clip_shape(&circle, &frame());
+        match &clipped[..] {
+            [Shape::Polygon(fill, fill_style), Shape::PolyLine(outline, false, stroke_style)] => {
+                assert_eq!((fill_style.fill, fill_style.stroke), (Some(Colour::RED), None));
+                assert_eq!(stroke_style, &Style::stroke(1.0, Colour::BLACK));
+                assert!(fill.iter().chain(outline.iter()).all(|p| p.x <= 20.0 + EPSILON && p.y <= 20.0 + EPSILON));
+                assert!(fill.contains(&Vector::new(20.0, 20.0)));
+            }
+            _ => panic!("{:?}", clipped),
+        }
+    }
+
+    #[test]
+    fn text_outside_is_dropped() {
+        let text = Shape::new_text("out", Vector::new(25.0, 15.0), 10.0, Anchor::Centre, Style::fill(Colour::RED));
+        assert_eq!(clip_shape(&text, &frame()), vec![]);
+    }
+}
@@ -0,0 +1,64 @@
+// RGBA with each channel in 0.0..=1.0, kept independent of any windowing library
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Colour {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Colour {
+    pub const TRANSPARENT: Colour = Colour::from_rgba(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Colour = Colour::from_rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Colour = Colour::from_rgb(1.0, 1.0, 1.0);
+    pub const GRAY: Colour = Colour::from_rgb(0.5, 0.5, 0.5);
+    pub const RED: Colour = Colour::from_rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Colour = Colour::from_rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Colour = Colour::from_rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Colour = Colour::from_rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Colour = Colour::from_rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Colour = Colour::from_rgb(1.0, 0.0, 1.0);
+
+    pub const fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        Colour { r, g, b, a: 1.0 }
+    }
+
+    pub const fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Colour { r, g, b, a }
+    }
+
+    pub fn from_int_rgb(r: u8, g: u8, b: u8) -> Self {
+        Colour::from_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    pub fn to_int_rgba(self) -> [u8; 4] {
+        [to_byte(self.r), to_byte(self.g), to_byte(self.b), to_byte(self.a)]
+    }
+}
+
+pub fn to_byte(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(feature = "window")]
+impl From<Colour> for speedy2d::color::Color {
+    fn from(c: Colour) -> Self {
+        speedy2d::color::Color::from_rgba(c.r, c.g, c.b, c.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_int_rgb() {
+        assert_eq!(Colour::from_int_rgb(255, 0, 51), Colour::from_rgb(1.0, 0.0, 0.2));
+    }
+
+    #[test]
+    fn to_int_rgba() {
+        assert_eq!(Colour::from_rgba(1.0, 0.0, 0.2, 0.5).to_int_rgba(), [255, 0, 51, 128]);
+        assert_eq!(Colour::from_rgb(2.0, -1.0, 0.0).to_int_rgba(), [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::vector::Vector;
+
+// SICP 2.2.4: left in the left half of left's frame, right in the right half of right's.
+// The result is in left's frame.
+pub fn beside(left: &CompositePainter, right: &CompositePainter) -> CompositePainter {
+    let split = Vector::new(0.5, 0.0);
+    left.transform_painter(Vector::zero(), split, Vector::new(0.0, 1.0))
+        .join(&right.transform_painter(split, Vector::new(1.0, 0.0), Vector::new(0.5, 1.0)))
+        .with_frame(left.frame())
+}
+
+// SICP 2.2.4: bottom in the half of its frame nearest the origin (y from 0 to 0.5), top in the other.
+// The result is in bottom's frame.
+pub fn below(bottom: &CompositePainter, top: &CompositePainter) -> CompositePainter {
+    let split = Vector::new(0.0, 0.5);
+    bottom.transform_painter(Vector::zero(), Vector::new(1.0, 0.0), split)
+        .join(&top.transform_painter(split, Vector::new(1.0, 0.5), Vector::new(0.0, 1.0)))
+        .with_frame(bottom.frame())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::frame::Frame;
+    use crate::picture_language::painter::Painter;
+
+    fn painter() -> Painter {
+        Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                Vector::new(100.0, 0.0),
+                                Vector::new(0.0, 200.0)))
+    }
+
+    fn frames(composite: &CompositePainter) -> Vec<Frame> {
+        composite.painters().iter().map(|p| p.frame).collect()
+    }
+
+    #[test]
+    fn beside_splits_horizontally() {
+        let p: CompositePainter = painter().into();
+        assert_eq!(frames(&beside(&p, &p)), vec![
+            Frame::new(Vector::new(10.0, 20.0), Vector::new(50.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(60.0, 20.0), Vector::new(50.0, 0.0), Vector::new(0.0, 200.0)),
+        ]);
+    }
+
+    #[test]
+    fn below_splits_vertically() {
+        let p: CompositePainter = painter().into();
+        assert_eq!(frames(&below(&p, &p)), vec![
+            Frame::new(Vector::new(10.0, 20.0), Vector::new(100.0, 0.0), Vector::new(0.0, 100.0)),
+            Frame::new(Vector::new(10.0, 120.0), Vector::new(100.0, 0.0), Vector::new(0.0, 100.0)),
+        ]);
+    }
+
+    #[test]
+    fn beside_nests() {
+        let p: CompositePainter = painter().into();
+        let quarters = beside(&p, &below(&p, &p));
+        assert_eq!(frames(&quarters)[2],
+                   Frame::new(Vector::new(60.0, 120.0), Vector::new(50.0, 0.0), Vector::new(0.0, 100.0)));
+    }
+
+    #[test]
+    fn nested_composites_move_together() {
+        let p: CompositePainter = painter().into();
+        let halves = beside(&p, &p);
+        let flipped = halves.flip_horiz();
+        assert_eq!(flipped.frame(), painter().frame);
+        assert_eq!(frames(&flipped), vec![
+            Frame::new(Vector::new(110.0, 20.0), Vector::new(-50.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(60.0, 20.0), Vector::new(-50.0, 0.0), Vector::new(0.0, 200.0)),
+        ]);
+    }
+
+    // A flipped or rotated composite still takes its own half, with its contents turned
+    #[test]
+    fn flipped_composites_side_by_side() {
+        let p: CompositePainter = painter().into();
+        let halves = beside(&p.flip_horiz(), &p);
+        assert_eq!(frames(&halves), vec![
+            Frame::new(Vector::new(60.0, 20.0), Vector::new(-50.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(60.0, 20.0), Vector::new(50.0, 0.0), Vector::new(0.0, 200.0)),
+        ]);
+        let halves = below(&p.rotate180(), &p);
+        assert_eq!(frames(&halves), vec![
+            Frame::new(Vector::new(110.0, 120.0), Vector::new(-100.0, 0.0), Vector::new(0.0, -100.0)),
+            Frame::new(Vector::new(10.0, 120.0), Vector::new(100.0, 0.0), Vector::new(0.0, 100.0)),
+        ]);
+        for composite in [beside(&p.flip_horiz(), &p), below(&p.rotate180(), &p)] {
+            let [first, second] = composite.painters() else { panic!("two painters") };
+            let (first, second) = (first.frame.bounds(), second.frame.bounds());
+            assert!(first.max.x <= second.min.x || first.max.y <= second.min.y);
+            assert_eq!(first.union(&second), painter().frame.bounds());
+        }
+    }
+
+    #[test]
+    fn flips() {
+        let p = painter();
+        assert_eq!(p.flip_vert().frame,
+                   Frame::new(Vector::new(10.0, 220.0), Vector::new(100.0, 0.0), Vector::new(0.0, -200.0)));
+        assert_eq!(p.flip_horiz().frame,
+                   Frame::new(Vector::new(110.0, 20.0), Vector::new(-100.0, 0.0), Vector::new(0.0, 200.0)));
+    }
+
+    #[test]
+    fn rotations() {
+        let p = painter();
+        assert_eq!(p.rotate90().frame,
+                   Frame::new(Vector::new(110.0, 20.0), Vector::new(0.0, 200.0), Vector::new(-100.0, 0.0)));
+        assert_eq!(p.rotate90().rotate90().frame, p.rotate180().frame);
+        assert_eq!(p.rotate180().rotate90().frame, p.rotate270().frame);
+        assert_eq!(p.rotate270().rotate90().frame, p.frame);
+    }
+
+    #[test]
+    fn shrink_to_upper_right() {
+        assert_eq!(painter().shrink_to_upper_right().frame,
+                   Frame::new(Vector::new(60.0, 120.0), Vector::new(50.0, 0.0), Vector::new(0.0, 100.0)));
+    }
+
+    #[test]
+    fn squash_inwards() {
+        let frame = painter().squash_inwards().frame;
+        assert_eq!(frame.map(Vector::zero()), Vector::new(10.0, 20.0));
+        assert_eq!(frame.map(Vector::new(1.0, 0.0)), Vector::new(75.0, 90.0));
+        assert_eq!(frame.map(Vector::new(0.0, 1.0)), Vector::new(45.0, 150.0));
+    }
+
+    #[test]
+    fn beside_composites() {
+        let p: CompositePainter = painter().into();
+        let halves = beside(&p, &p);
+        let quarters = beside(&halves, &halves);
+        assert_eq!(quarters.frame(), painter().frame);
+        assert_eq!(frames(&quarters), vec![
+            Frame::new(Vector::new(10.0, 20.0), Vector::new(25.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(35.0, 20.0), Vector::new(25.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(60.0, 20.0), Vector::new(25.0, 0.0), Vector::new(0.0, 200.0)),
+            Frame::new(Vector::new(85.0, 20.0), Vector::new(25.0, 0.0), Vector::new(0.0, 200.0)),
+        ]);
+    }
+}
@@ -1,27 +1,139 @@
+use std::rc::Rc;
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::curve::Curve;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
 use crate::picture_language::painter::Painter;
 use crate::picture_language::segment::Segment;
-use crate::picture_language::shape::Shape;
+use crate::picture_language::shape::{Anchor, Shape};
+use crate::picture_language::style::Style;
 use crate::picture_language::vector::Vector;
-use speedy2d::color::Color;
 
+// The frame is the one the painters were laid out in, so that the combinators move them
+// all together. It starts as the first painter's frame.
 #[derive(Debug, PartialEq, Clone)]
 pub struct CompositePainter {
+    frame: Frame,
     painters: Vec<Painter>,
 }
 
 impl CompositePainter {
     pub fn new(painters: Vec<Painter>) -> Self {
-        Self { painters }
+        let frame = painters.first()
+            .map(|p| p.frame)
+            .unwrap_or(Frame::new(Vector::zero(), Vector::new(1.0, 0.0), Vector::new(0.0, 1.0)));
+        Self { frame, painters }
     }
 
-    pub fn render_lines(&mut self, unit_segments: &Vec<Segment>, thickness: f32, colour: Color) {
+    pub fn frame(&self) -> Frame {
+        self.frame
+    }
+
+    pub fn painters(&self) -> &[Painter] {
+        &self.painters
+    }
+
+    pub fn with_frame(&self, frame: Frame) -> Self {
+        Self { frame, painters: self.painters.clone() }
+    }
+
+    // Each painter is clipped to its own frame
+    pub fn clipped(&self) -> Self {
+        let painters = self.painters.iter().cloned().map(Painter::clipped).collect();
+        Self { frame: self.frame, painters }
+    }
+
+    // Keeps this composite's frame
+    pub fn join(&self, other: &CompositePainter) -> Self {
+        let painters = self.painters.iter().chain(other.painters.iter()).cloned().collect();
+        Self { frame: self.frame, painters }
+    }
+
+    pub fn transform_painter(&self, origin: Vector, corner1: Vector, corner2: Vector) -> Self {
+        self.moved_to(self.frame.transform_painter(origin, corner1, corner2))
+    }
+
+    pub fn flip_vert(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).flip_vert().frame)
+    }
+
+    pub fn flip_horiz(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).flip_horiz().frame)
+    }
+
+    pub fn rotate90(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).rotate90().frame)
+    }
+
+    pub fn rotate180(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).rotate180().frame)
+    }
+
+    pub fn rotate270(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).rotate270().frame)
+    }
+
+    pub fn shrink_to_upper_right(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).shrink_to_upper_right().frame)
+    }
+
+    pub fn squash_inwards(&self) -> Self {
+        self.contents_moved_to(Painter::new(self.frame).squash_inwards().frame)
+    }
+
+    // The painters move and the frame stays, so that beside and below still lay the composite
+    // out in its own place
+    fn contents_moved_to(&self, frame: Frame) -> Self {
+        self.moved_to(frame).with_frame(self.frame)
+    }
+
+    // Each painter keeps its place relative to the frame. A flat frame has no places to keep,
+    // so the painters all take the new frame.
+    fn moved_to(&self, frame: Frame) -> Self {
+        let painters = self.painters.iter()
+            .map(|p| p.with_frame(self.frame.unmap_frame(&p.frame).map_or(frame, |unit| frame.map_frame(&unit))))
+            .collect();
+        Self { frame, painters }
+    }
+
+    pub fn render_lines(&mut self, unit_segments: &Vec<Segment>, style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_lines(unit_segments, style));
+    }
+
+    pub fn render_path(&mut self, unit_points: &[Vector], closed: bool, style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_path(unit_points, closed, style));
+    }
+
+    pub fn render_curves(&mut self, unit_curves: &[Curve], closed: bool, style: &Style) {
         self.painters.iter_mut()
-            .for_each(|p| p.render_lines(unit_segments, thickness, colour));
+            .for_each(|p| p.render_curves(unit_curves, closed, style));
     }
 
-    pub fn render_circle(&mut self, centre: &Vector, radius: f32, colour: Color) {
+    pub fn render_image(&mut self, image: &Rc<Image>) {
         self.painters.iter_mut()
-            .for_each(|p| p.render_circle(centre, radius, colour));
+            .for_each(|p| p.render_image(image));
+    }
+
+    pub fn render_text(&mut self, text: &str, unit_position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_text(text, unit_position, size, anchor, style));
+    }
+
+    pub fn render_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_circle(centre, radius, style));
+    }
+
+    pub fn render_polygon(&mut self, unit_points: &[Vector], style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_polygon(unit_points, style));
+    }
+
+    pub fn render_rect(&mut self, unit_min: &Vector, unit_max: &Vector, style: &Style) {
+        self.painters.iter_mut()
+            .for_each(|p| p.render_rect(unit_min, unit_max, style));
     }
 
     pub fn paint(&mut self) -> Vec<Shape> {
@@ -31,25 +143,81 @@ impl CompositePainter {
         result
     }
 
+    // Around all the painters' frames, or this composite's own frame if it has no painters
+    pub fn bounds(&self) -> Bounds {
+        Bounds::union_all(self.painters.iter().map(Painter::bounds)).unwrap_or(self.frame.bounds())
+    }
+
+    pub fn content_bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.painters.iter().filter_map(Painter::content_bounds))
+    }
+
+    // A copy placed edge to edge with this one's bounds
     pub fn to_right(&self) -> Self {
-        let x_max: f32 = self.painters.iter()
-            .map(|p| p.frame.x_max())
-            .fold(0.0, |x1, x2| x1.max(x2));
-        self.transform(Vector::new(x_max, 0.0))
+        self.transform(Vector::new(self.bounds().width(), 0.0))
     }
 
     pub fn to_below(&self) -> Self {
-        let y_max: f32 = self.painters.iter()
-            .map(|p| p.frame.y_max())
-            .fold(0.0, |y1, y2| y1.max(y2));
-        self.transform(Vector::new(0.0, y_max))
+        self.transform(Vector::new(0.0, self.bounds().height()))
+    }
+
+    pub fn to_left(&self) -> Self {
+        self.transform(Vector::new(-self.bounds().width(), 0.0))
+    }
+
+    pub fn to_above(&self) -> Self {
+        self.transform(Vector::new(0.0, -self.bounds().height()))
+    }
+
+    // Moved so that the top left of its bounds is at the point
+    pub fn placed_at(&self, top_left: Vector) -> Self {
+        self.transform(top_left - self.bounds().min)
     }
 
     fn transform(&self, v: Vector) -> Self {
-        CompositePainter::new(self.painters
+        let painters = self.painters
             .iter()
-            .map(|p|
-                Painter::new(p.frame.transform(v)))
-            .collect())
+            .map(|p| p.with_frame(p.frame.transform(v)))
+            .collect();
+        Self { frame: self.frame.transform(v), painters }
+    }
+}
+
+impl From<Painter> for CompositePainter {
+    fn from(painter: Painter) -> Self {
+        CompositePainter::new(vec![painter])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two cells side by side, to the left of and above the origin
+    fn composite() -> CompositePainter {
+        let painter = Painter::new(Frame::new(Vector::new(-300.0, -100.0),
+                                              Vector::new(100.0, 0.0),
+                                              Vector::new(0.0, 50.0)));
+        CompositePainter::new(vec![painter.clone(), painter.right()])
+    }
+
+    fn origins(composite: &CompositePainter) -> Vec<Vector> {
+        composite.painters().iter().map(|p| p.frame.origin()).collect()
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(composite().bounds(), Bounds::new(Vector::new(-300.0, -100.0), Vector::new(-100.0, -50.0)));
+        assert_eq!(CompositePainter::new(vec![]).bounds(), Bounds::new(Vector::zero(), Vector::one()));
+    }
+
+    #[test]
+    fn placement_in_negative_space() {
+        assert_eq!(origins(&composite().to_right()), vec![Vector::new(-100.0, -100.0), Vector::new(0.0, -100.0)]);
+        assert_eq!(origins(&composite().to_below()), vec![Vector::new(-300.0, -50.0), Vector::new(-200.0, -50.0)]);
+        assert_eq!(origins(&composite().to_left()), vec![Vector::new(-500.0, -100.0), Vector::new(-400.0, -100.0)]);
+        assert_eq!(origins(&composite().to_above()), vec![Vector::new(-300.0, -150.0), Vector::new(-200.0, -150.0)]);
+        assert_eq!(origins(&composite().placed_at(Vector::new(10.0, 20.0))),
+                   vec![Vector::new(10.0, 20.0), Vector::new(110.0, 20.0)]);
     }
 }
@@ -0,0 +1,77 @@
+use crate::picture_language::frame::Frame;
+use crate::picture_language::shape::Shape;
+use crate::picture_language::transform::Transform;
+use crate::picture_language::vector::Vector;
+
+// Which way a picture's y axis points. Devices (windows, SVG and PNG) put the origin at the
+// top left with y down. SICP puts it at the bottom left with y up, so that below, flip-vert
+// and the published figures come out as in the book.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CoordinateSystem {
+    YDown,
+    YUp,
+}
+
+impl CoordinateSystem {
+    // From the picture to a device of the given height
+    pub fn to_device(self, height: f32) -> Transform {
+        match self {
+            CoordinateSystem::YDown => Transform::IDENTITY,
+            CoordinateSystem::YUp =>
+                Transform::new(Vector::new(1.0, 0.0), Vector::new(0.0, -1.0), Vector::new(0.0, height)),
+        }
+    }
+
+    // Text stays upright, as Shape::transformed only moves it
+    pub fn shapes_to_device(self, shapes: &[Shape], height: f32) -> Vec<Shape> {
+        match self {
+            CoordinateSystem::YDown => shapes.to_vec(),
+            CoordinateSystem::YUp => {
+                let transform = self.to_device(height);
+                shapes.iter().map(|shape| self.transform_shape(shape, &transform)).collect()
+            }
+        }
+    }
+
+    // Shape::transformed by a transform to the device, keeping images upright like text. With y up
+    // an image's first row is along the top of its frame, so it is flipped back to the top once y
+    // points down.
+    pub fn transform_shape(self, shape: &Shape, transform: &Transform) -> Shape {
+        match (self, shape.transformed(transform)) {
+            (CoordinateSystem::YUp, Shape::Image(image, frame, opacity)) => {
+                let (origin, edge1, edge2) = (frame.origin(), frame.edge1(), frame.edge2());
+                Shape::Image(image, Frame::new(origin + edge2, edge1, Vector::zero() - edge2), opacity)
+            }
+            (_, shape) => shape,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+    use crate::picture_language::shape::Anchor;
+    use crate::picture_language::style::Style;
+
+    #[test]
+    fn to_device() {
+        let p = Vector::new(10.0, 20.0);
+        assert_eq!(CoordinateSystem::YDown.to_device(100.0).apply(p), p);
+        assert_eq!(CoordinateSystem::YUp.to_device(100.0).apply(p), Vector::new(10.0, 80.0));
+    }
+
+    #[test]
+    fn shapes_to_device() {
+        let style = Style::fill(Colour::RED);
+        let shapes = vec![
+            Shape::new_rect(Vector::zero(), Vector::new(10.0, 20.0), style.clone()),
+            Shape::new_text("up", Vector::new(5.0, 90.0), 10.0, Anchor::TopLeft, style.clone()),
+        ];
+        assert_eq!(CoordinateSystem::YDown.shapes_to_device(&shapes, 100.0), shapes);
+        assert_eq!(CoordinateSystem::YUp.shapes_to_device(&shapes, 100.0), vec![
+            Shape::new_rect(Vector::new(0.0, 80.0), Vector::new(10.0, 100.0), style.clone()),
+            Shape::new_text("up", Vector::new(5.0, 10.0), 10.0, Anchor::TopLeft, style),
+        ]);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::picture_language::frame::Frame;
+use crate::picture_language::vector::Vector;
+
+// Flattened curves are within this distance of the true curve, in device units
+pub const TOLERANCE: f32 = 0.25;
+
+// Deep enough for any curve on screen; stops runaway subdivision of huge or degenerate curves
+const MAX_DEPTH: u32 = 16;
+
+// One piece of a path. Arcs are in the same form as Shape::Ellipse, the points
+// centre + axis1 * cos(t) + axis2 * sin(t) for t from the start angle to the end angle,
+// which is what a circular arc becomes in a skewed frame.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Curve {
+    Line(Vector, Vector),
+    Quadratic(Vector, Vector, Vector), // start, control, end
+    Cubic(Vector, Vector, Vector, Vector), // start, two controls, end
+    Arc(Vector, Vector, Vector, f32, f32), // centre, axis1, axis2, start and end angles
+}
+
+impl Curve {
+    // Angles are in radians, anticlockwise in the unit square
+    pub fn circular_arc(centre: Vector, radius: f32, start_angle: f32, end_angle: f32) -> Self {
+        Curve::Arc(centre, Vector::new(radius, 0.0), Vector::new(0.0, radius), start_angle, end_angle)
+    }
+
+    pub fn start(&self) -> Vector {
+        self.point_at(0.0)
+    }
+
+    pub fn end(&self) -> Vector {
+        self.point_at(1.0)
+    }
+
+    // t runs from 0.0 at the start to 1.0 at the end
+    pub fn point_at(&self, t: f32) -> Vector {
+        let s = 1.0 - t;
+        match self {
+            Curve::Line(start, end) => start.scale(s) + end.scale(t),
+            Curve::Quadratic(p0, p1, p2) => p0.scale(s * s) + p1.scale(2.0 * s * t) + p2.scale(t * t),
+            Curve::Cubic(p0, p1, p2, p3) =>
+                p0.scale(s * s * s) + p1.scale(3.0 * s * s * t) + p2.scale(3.0 * s * t * t) + p3.scale(t * t * t),
+            Curve::Arc(centre, axis1, axis2, start, end) => {
+                let angle = start + (end - start) * t;
+                *centre + axis1.scale(angle.cos()) + axis2.scale(angle.sin())
+            }
+        }
+    }
+
+    // Affine maps take Béziers to Béziers and ellipses to ellipses, so mapping the
+    // defining points is exact
+    pub fn map(&self, frame: &Frame) -> Self {
+        let map = |v: &Vector| frame.map(*v);
+        match self {
+            Curve::Line(start, end) => Curve::Line(map(start), map(end)),
+            Curve::Quadratic(p0, p1, p2) => Curve::Quadratic(map(p0), map(p1), map(p2)),
+            Curve::Cubic(p0, p1, p2, p3) => Curve::Cubic(map(p0), map(p1), map(p2), map(p3)),
+            Curve::Arc(centre, axis1, axis2, start, end) => {
+                let direction = |v: &Vector| frame.map(*v) - frame.origin();
+                Curve::Arc(map(centre), direction(axis1), direction(axis2), *start, *end)
+            }
+        }
+    }
+
+    // Points from the start to the end, with the polyline through them within tolerance of the curve
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vector> {
+        match self {
+            Curve::Line(start, end) => vec![*start, *end],
+            Curve::Quadratic(p0, p1, p2) => {
+                // Raised to a cubic, which has the same shape
+                let c1 = *p0 + (*p1 - *p0).scale(2.0 / 3.0);
+                let c2 = *p2 + (*p1 - *p2).scale(2.0 / 3.0);
+                Curve::Cubic(*p0, c1, c2, *p2).flatten(tolerance)
+            }
+            Curve::Cubic(p0, p1, p2, p3) => {
+                let mut points = vec![*p0];
+                flatten_cubic([*p0, *p1, *p2, *p3], tolerance, 0, &mut points);
+                points
+            }
+            Curve::Arc(_, axis1, axis2, start, end) => {
+                let radius = axis1.length().max(axis2.length());
+                let step = if radius > tolerance { 2.0 * (1.0 - tolerance / radius).acos() } else { std::f32::consts::PI };
+                let count = (((end - start).abs() / step).ceil() as usize).clamp(1, 1024);
+                (0..=count).map(|i| self.point_at(i as f32 / count as f32)).collect()
+            }
+        }
+    }
+}
+
+// The curves one after another as a single polyline. A curve that does not start where the
+// last one ended is joined to it by a straight line.
+pub fn flatten_path(curves: &[Curve], tolerance: f32) -> Vec<Vector> {
+    let mut points: Vec<Vector> = vec![];
+    for curve in curves {
+        let flattened = curve.flatten(tolerance);
+        let skip = if points.last() == flattened.first() { 1 } else { 0 };
+        points.extend(flattened.into_iter().skip(skip));
+    }
+    points
+}
+
+// Appends the points after the first, splitting in half until the control points are within
+// tolerance of the chord
+fn flatten_cubic(p: [Vector; 4], tolerance: f32, depth: u32, points: &mut Vec<Vector>) {
+    if depth >= MAX_DEPTH || (distance_to_line(p[1], p[0], p[3]) <= tolerance
+        && distance_to_line(p[2], p[0], p[3]) <= tolerance) {
+        points.push(p[3]);
+        return;
+    }
+    // de Casteljau
+    let midpoint = |a: Vector, b: Vector| (a + b).scale(0.5);
+    let (p01, p12, p23) = (midpoint(p[0], p[1]), midpoint(p[1], p[2]), midpoint(p[2], p[3]));
+    let (p012, p123) = (midpoint(p01, p12), midpoint(p12, p23));
+    let middle = midpoint(p012, p123);
+    flatten_cubic([p[0], p01, p012, middle], tolerance, depth + 1, points);
+    flatten_cubic([middle, p123, p23, p[3]], tolerance, depth + 1, points);
+}
+
+// From p to the segment from start to end, or to start if the segment is a point
+fn distance_to_line(p: Vector, start: Vector, end: Vector) -> f32 {
+    let chord = end - start;
+    let length = chord.length();
+    let offset = p - start;
+    if length == 0.0 {
+        offset.length()
+    } else {
+        (offset.x * chord.y - offset.y * chord.x).abs() / length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn close(v1: Vector, v2: Vector) -> bool {
+        (v1 - v2).length() < 1e-4
+    }
+
+    #[test]
+    fn end_points() {
+        let cubic = Curve::Cubic(Vector::zero(), Vector::new(1.0, 2.0), Vector::new(3.0, 2.0), Vector::new(4.0, 0.0));
+        assert_eq!(cubic.start(), Vector::zero());
+        assert_eq!(cubic.end(), Vector::new(4.0, 0.0));
+        assert_eq!(cubic.point_at(0.5), Vector::new(2.0, 1.5));
+        let quadratic = Curve::Quadratic(Vector::zero(), Vector::new(1.0, 2.0), Vector::new(2.0, 0.0));
+        assert_eq!(quadratic.point_at(0.5), Vector::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn circular_arc() {
+        let arc = Curve::circular_arc(Vector::new(1.0, 1.0), 2.0, 0.0, PI / 2.0);
+        assert!(close(arc.start(), Vector::new(3.0, 1.0)));
+        assert!(close(arc.end(), Vector::new(1.0, 3.0)));
+    }
+
+    #[test]
+    fn map_through_a_frame() {
+        let frame = Frame::new(Vector::new(10.0, 20.0), Vector::new(100.0, 20.0), Vector::new(20.0, 200.0));
+        let cubic = Curve::Cubic(Vector::zero(), Vector::new(0.2, 0.9), Vector::new(0.7, 0.1), Vector::one());
+        let mapped = cubic.map(&frame);
+        (0..=10).map(|i| i as f32 / 10.0)
+            .for_each(|t| assert!(close(mapped.point_at(t), frame.map(cubic.point_at(t)))));
+        let arc = Curve::circular_arc(Vector::new(0.5, 0.5), 0.25, 0.3, 2.0);
+        let mapped = arc.map(&frame);
+        (0..=10).map(|i| i as f32 / 10.0)
+            .for_each(|t| assert!(close(mapped.point_at(t), frame.map(arc.point_at(t)))));
+    }
+
+    #[test]
+    fn flattened_cubic_is_within_tolerance() {
+        let cubic = Curve::Cubic(Vector::zero(), Vector::new(0.0, 100.0), Vector::new(100.0, 100.0), Vector::new(100.0, 0.0));
+        let points = cubic.flatten(TOLERANCE);
+        assert!(points.len() > 4);
+        assert_eq!(points.first(), Some(&Vector::zero()));
+        assert_eq!(points.last(), Some(&Vector::new(100.0, 0.0)));
+        // Every point on the curve is near the polyline
+        (0..=100).map(|i| cubic.point_at(i as f32 / 100.0)).for_each(|p| {
+            let nearest = points.iter().zip(points.iter().skip(1))
+                .map(|(a, b)| {
+                    let chord = *b - *a;
+                    let t = ((p.x - a.x) * chord.x + (p.y - a.y) * chord.y) / (chord.x * chord.x + chord.y * chord.y);
+                    (*a + chord.scale(t.clamp(0.0, 1.0)) - p).length()
+                })
+                .fold(f32::MAX, f32::min);
+            assert!(nearest <= TOLERANCE * 1.01, "{:?} is {} away", p, nearest);
+        });
+    }
+
+    #[test]
+    fn straight_curves_flatten_to_their_ends() {
+        let cubic = Curve::Cubic(Vector::zero(), Vector::new(1.0, 1.0), Vector::new(2.0, 2.0), Vector::new(3.0, 3.0));
+        assert_eq!(cubic.flatten(TOLERANCE), vec![Vector::zero(), Vector::new(3.0, 3.0)]);
+    }
+
+    #[test]
+    fn flattened_arc() {
+        let arc = Curve::circular_arc(Vector::zero(), 100.0, 0.0, PI);
+        let points = arc.flatten(TOLERANCE);
+        assert!(points.len() > 10);
+        assert!(points.iter().all(|p| (p.length() - 100.0).abs() < 1e-3));
+        assert!(close(*points.last().unwrap(), Vector::new(-100.0, 0.0)));
+    }
+
+    #[test]
+    fn flatten_path_joins_curves() {
+        let path = [
+            Curve::Line(Vector::zero(), Vector::new(1.0, 0.0)),
+            Curve::Line(Vector::new(1.0, 0.0), Vector::new(1.0, 1.0)),
+            Curve::Line(Vector::new(2.0, 2.0), Vector::new(3.0, 2.0)),
+        ];
+        assert_eq!(flatten_path(&path, TOLERANCE), vec![
+            Vector::zero(), Vector::new(1.0, 0.0), Vector::new(1.0, 1.0), Vector::new(2.0, 2.0), Vector::new(3.0, 2.0),
+        ]);
+    }
+}
@@ -0,0 +1,571 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::rc::Rc;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::combinators;
+use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::painter::Painter;
+use crate::picture_language::patterns;
+use crate::picture_language::picture;
+use crate::picture_language::picture_file;
+use crate::picture_language::segment::Segment;
+use crate::picture_language::sexpr::{self, Expr};
+use crate::picture_language::style::Style;
+use crate::picture_language::vector::Vector;
+
+// A small Scheme for writing pictures the way SICP does, such as
+//
+//   (define (flipped-pairs painter)
+//     (let ((painter2 (beside painter (flip-vert painter))))
+//       (below painter2 painter2)))
+//   (square-limit (flipped-pairs wave) 4)
+//
+// As in SICP a painter is a procedure of a frame: it draws into whatever frame it is given,
+// and the combinators make new painters that hand their parts smaller or transformed frames.
+// The special forms are define, lambda, let, if and begin.
+
+// Draws into the frame, giving the painters it drew with
+pub type Paint = Rc<dyn Fn(&Frame) -> CompositePainter>;
+
+type Builtin = fn(&[Value]) -> io::Result<Value>;
+
+#[derive(Clone)]
+pub enum Value {
+    Number(f32),
+    Bool(bool),
+    Str(String),
+    Vector(Vector),
+    Segment(Segment),
+    List(Vec<Value>),
+    Painter(Paint),
+    Procedure(Rc<Procedure>),
+    Builtin(&'static str, Builtin),
+}
+
+pub struct Procedure {
+    parameters: Vec<String>,
+    body: Vec<Expr>,
+    env: Rc<Env>,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Bool(b) => write!(f, "{}", if *b { "#t" } else { "#f" }),
+            Value::Str(text) => write!(f, "{}", Expr::Str(text.clone())),
+            Value::Vector(v) => write!(f, "#<vect {} {}>", v.x, v.y),
+            Value::Segment(s) => write!(f, "#<segment {} {} {} {}>", s.start.x, s.start.y, s.end.x, s.end.y),
+            Value::List(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { " " } else { "" }, item)?;
+                }
+                write!(f, ")")
+            }
+            Value::Painter(_) => write!(f, "#<painter>"),
+            Value::Procedure(_) => write!(f, "#<procedure>"),
+            Value::Builtin(name, _) => write!(f, "#<procedure {}>", name),
+        }
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[derive(Default)]
+struct Env {
+    variables: RefCell<HashMap<String, Value>>,
+    parent: Option<Rc<Env>>,
+}
+
+impl Env {
+    fn lookup(&self, name: &str) -> io::Result<Value> {
+        match (self.variables.borrow().get(name), &self.parent) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(parent)) => parent.lookup(name),
+            (None, None) => Err(invalid(&format!("unbound variable {}", name))),
+        }
+    }
+
+    fn define(&self, name: &str, value: Value) {
+        self.variables.borrow_mut().insert(name.to_string(), value);
+    }
+}
+
+// Definitions last as long as the interpreter, so a session can build on earlier ones
+pub struct Interpreter {
+    global: Rc<Env>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let global = Rc::new(Env::default());
+        for (name, builtin) in BUILTINS {
+            global.define(name, Value::Builtin(name, *builtin));
+        }
+        global.define("outline", path_painter(picture::outer_bounds()));
+        global.define("diamond", path_painter(picture::diamond()));
+        global.define("cross", segments_painter(picture::cross()));
+        global.define("wave", segments_painter(picture::wave()));
+        let mut interpreter = Interpreter { global };
+        interpreter.run(PRELUDE).expect("the prelude is valid");
+        interpreter
+    }
+
+    // Evaluates each expression in the source, giving the value of the last
+    pub fn run(&mut self, source: &str) -> io::Result<Value> {
+        let mut value = Value::List(vec![]);
+        for expr in sexpr::parse_all(source)? {
+            value = self.eval(&expr)?;
+        }
+        Ok(value)
+    }
+
+    pub fn eval(&mut self, expr: &Expr) -> io::Result<Value> {
+        eval(expr, &self.global)
+    }
+
+    // Runs the source, whose last expression must be a painter, and paints it into the frame
+    pub fn paint(&mut self, source: &str, frame: Frame) -> io::Result<CompositePainter> {
+        match self.run(source)? {
+            Value::Painter(paint) => Ok(paint(&frame)),
+            value => Err(invalid(&format!("a picture must end with a painter, not {}", value))),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+pub fn paint_file(path: &str, frame: Frame) -> io::Result<CompositePainter> {
+    let source = fs::read_to_string(path)?;
+    Interpreter::new().paint(&source, frame).map_err(|error| invalid(&format!("{}: {}", path, error)))
+}
+
+fn eval(expr: &Expr, env: &Rc<Env>) -> io::Result<Value> {
+    match expr {
+        Expr::Number(number) => Ok(Value::Number(*number)),
+        Expr::Str(text) => Ok(Value::Str(text.clone())),
+        Expr::Symbol(name) => match name.as_str() {
+            "#t" => Ok(Value::Bool(true)),
+            "#f" => Ok(Value::Bool(false)),
+            _ => env.lookup(name),
+        },
+        Expr::List(items) => match &items[..] {
+            [] => Err(invalid("cannot evaluate ()")),
+            [Expr::Symbol(form), rest @ ..] if SPECIAL_FORMS.contains(&form.as_str()) =>
+                special_form(form, rest, expr, env),
+            [operator, operands @ ..] => {
+                let procedure = eval(operator, env)?;
+                let arguments = operands.iter().map(|operand| eval(operand, env)).collect::<io::Result<Vec<_>>>()?;
+                apply(&procedure, &arguments).map_err(|error| invalid(&format!("in {}: {}", operator, error)))
+            }
+        },
+    }
+}
+
+const SPECIAL_FORMS: [&str; 5] = ["define", "lambda", "let", "if", "begin"];
+
+fn special_form(form: &str, rest: &[Expr], expr: &Expr, env: &Rc<Env>) -> io::Result<Value> {
+    match (form, rest) {
+        ("define", [Expr::Symbol(name), value]) => {
+            let value = eval(value, env)?;
+            env.define(name, value);
+            Ok(Value::List(vec![]))
+        }
+        ("define", [Expr::List(signature), body @ ..]) if !body.is_empty() => match &signature[..] {
+            [Expr::Symbol(name), parameters @ ..] => {
+                env.define(name, lambda(parameters, body, env)?);
+                Ok(Value::List(vec![]))
+            }
+            _ => Err(bad_form(expr)),
+        },
+        ("lambda", [Expr::List(parameters), body @ ..]) if !body.is_empty() => lambda(parameters, body, env),
+        ("let", [Expr::List(bindings), body @ ..]) if !body.is_empty() => {
+            let scope = Rc::new(Env { parent: Some(env.clone()), ..Env::default() });
+            for binding in bindings {
+                match binding.as_list() {
+                    Some([Expr::Symbol(name), value]) => scope.define(name, eval(value, env)?),
+                    _ => return Err(bad_form(expr)),
+                }
+            }
+            eval_body(body, &scope)
+        }
+        ("if", [condition, consequent, alternative @ ..]) if alternative.len() <= 1 => {
+            if !matches!(eval(condition, env)?, Value::Bool(false)) {
+                eval(consequent, env)
+            } else if let [alternative] = alternative {
+                eval(alternative, env)
+            } else {
+                Ok(Value::List(vec![]))
+            }
+        }
+        ("begin", body) if !body.is_empty() => eval_body(body, env),
+        _ => Err(bad_form(expr)),
+    }
+}
+
+fn lambda(parameters: &[Expr], body: &[Expr], env: &Rc<Env>) -> io::Result<Value> {
+    let parameters = parameters.iter()
+        .map(|parameter| parameter.as_symbol().map(str::to_string).ok_or_else(|| invalid("parameters must be names")))
+        .collect::<io::Result<_>>()?;
+    Ok(Value::Procedure(Rc::new(Procedure { parameters, body: body.to_vec(), env: env.clone() })))
+}
+
+fn eval_body(body: &[Expr], env: &Rc<Env>) -> io::Result<Value> {
+    let (last, init) = body.split_last().unwrap();
+    for expr in init {
+        eval(expr, env)?;
+    }
+    eval(last, env)
+}
+
+pub fn apply(procedure: &Value, arguments: &[Value]) -> io::Result<Value> {
+    match procedure {
+        Value::Builtin(_, builtin) => builtin(arguments),
+        Value::Procedure(procedure) => {
+            if procedure.parameters.len() != arguments.len() {
+                return Err(invalid(&format!("expected {} arguments, got {}", procedure.parameters.len(), arguments.len())));
+            }
+            let scope = Rc::new(Env { parent: Some(procedure.env.clone()), ..Env::default() });
+            for (parameter, argument) in procedure.parameters.iter().zip(arguments) {
+                scope.define(parameter, argument.clone());
+            }
+            eval_body(&procedure.body, &scope)
+        }
+        _ => Err(invalid(&format!("{} is not a procedure", procedure))),
+    }
+}
+
+// SICP's higher-order painter operations, written in the language itself
+const PRELUDE: &str = "
+(define (identity x) x)
+(define (square-of-four tl tr bl br)
+  (lambda (painter)
+    (let ((top (beside (tl painter) (tr painter)))
+          (bottom (beside (bl painter) (br painter))))
+      (below bottom top))))
+(define (flipped-pairs painter)
+  (let ((painter2 (beside painter (flip-vert painter))))
+    (below painter2 painter2)))
+(define (square-limit painter n)
+  (let ((combine4 (square-of-four flip-horiz identity rotate180 flip-vert)))
+    (combine4 (corner-split painter n))))
+";
+
+const BUILTINS: &[(&str, Builtin)] = &[
+    ("+", |args| Ok(Value::Number(numbers(args)?.iter().sum()))),
+    ("*", |args| Ok(Value::Number(numbers(args)?.iter().product()))),
+    ("-", |args| match &numbers(args)?[..] {
+        [x] => Ok(Value::Number(-x)),
+        [x, rest @ ..] => Ok(Value::Number(rest.iter().fold(*x, |a, b| a - b))),
+        [] => Err(invalid("expected at least 1 argument")),
+    }),
+    ("/", |args| match &numbers(args)?[..] {
+        [x] => Ok(Value::Number(1.0 / x)),
+        [x, rest @ ..] => Ok(Value::Number(rest.iter().fold(*x, |a, b| a / b))),
+        [] => Err(invalid("expected at least 1 argument")),
+    }),
+    ("=", |args| compare(args, |a, b| a == b)),
+    ("<", |args| compare(args, |a, b| a < b)),
+    (">", |args| compare(args, |a, b| a > b)),
+    ("<=", |args| compare(args, |a, b| a <= b)),
+    (">=", |args| compare(args, |a, b| a >= b)),
+    ("not", |args| match args {
+        [value] => Ok(Value::Bool(matches!(value, Value::Bool(false)))),
+        _ => Err(arity(1, args)),
+    }),
+    ("list", |args| Ok(Value::List(args.to_vec()))),
+    ("make-vect", |args| match &numbers(args)?[..] {
+        [x, y] => Ok(Value::Vector(Vector::new(*x, *y))),
+        _ => Err(arity(2, args)),
+    }),
+    ("xcor-vect", |args| Ok(Value::Number(vector(args, 0, 1)?.x))),
+    ("ycor-vect", |args| Ok(Value::Number(vector(args, 0, 1)?.y))),
+    ("add-vect", |args| Ok(Value::Vector(vector(args, 0, 2)? + vector(args, 1, 2)?))),
+    ("sub-vect", |args| Ok(Value::Vector(vector(args, 0, 2)? - vector(args, 1, 2)?))),
+    ("scale-vect", |args| Ok(Value::Vector(vector(args, 1, 2)?.scale(number(args, 0, 2)?)))),
+    ("make-segment", |args| Ok(Value::Segment(Segment::new(vector(args, 0, 2)?, vector(args, 1, 2)?)))),
+    ("start-segment", |args| Ok(Value::Vector(segment(args, 0, 1)?.start))),
+    ("end-segment", |args| Ok(Value::Vector(segment(args, 0, 1)?.end))),
+    ("segments->painter", |args| match args {
+        [Value::List(items)] => Ok(segments_painter(items.iter()
+            .map(|item| segment(std::slice::from_ref(item), 0, 1))
+            .collect::<io::Result<_>>()?)),
+        _ => Err(invalid("expected a list of segments")),
+    }),
+    // A picture file of segments, such as one of the tiles
+    ("load-segments", |args| match args {
+        [Value::Str(path)] => Ok(Value::List(picture_file::read_file::<Vec<Segment>>(path)?
+            .into_iter().map(Value::Segment).collect())),
+        _ => Err(invalid("expected a file name")),
+    }),
+    ("transform-painter", |args| {
+        let (origin, corner1, corner2) = (vector(args, 1, 4)?, vector(args, 2, 4)?, vector(args, 3, 4)?);
+        let paint = painter(args, 0, 4)?;
+        Ok(Value::Painter(Rc::new(move |frame| paint(&frame.transform_painter(origin, corner1, corner2)))))
+    }),
+    ("beside", |args| pair(painter(args, 0, 2)?, painter(args, 1, 2)?, combinators::beside)),
+    ("below", |args| pair(painter(args, 0, 2)?, painter(args, 1, 2)?, combinators::below)),
+    ("flip-vert", |args| single(painter(args, 0, 1)?, |p| p.flip_vert())),
+    ("flip-horiz", |args| single(painter(args, 0, 1)?, |p| p.flip_horiz())),
+    ("rotate90", |args| single(painter(args, 0, 1)?, |p| p.rotate90())),
+    ("rotate180", |args| single(painter(args, 0, 1)?, |p| p.rotate180())),
+    ("rotate270", |args| single(painter(args, 0, 1)?, |p| p.rotate270())),
+    ("shrink-to-upper-right", |args| single(painter(args, 0, 1)?, |p| p.shrink_to_upper_right())),
+    ("squash-inwards", |args| single(painter(args, 0, 1)?, |p| p.squash_inwards())),
+    ("right-split", |args| split(args, patterns::right_split)),
+    ("up-split", |args| split(args, patterns::up_split)),
+    ("corner-split", |args| split(args, patterns::corner_split)),
+];
+
+fn numbers(args: &[Value]) -> io::Result<Vec<f32>> {
+    args.iter().map(|arg| match arg {
+        Value::Number(number) => Ok(*number),
+        _ => Err(invalid(&format!("{} is not a number", arg))),
+    }).collect()
+}
+
+fn compare(args: &[Value], test: fn(f32, f32) -> bool) -> io::Result<Value> {
+    Ok(Value::Bool(numbers(args)?.windows(2).all(|pair| test(pair[0], pair[1]))))
+}
+
+fn arity(count: usize, args: &[Value]) -> io::Error {
+    invalid(&format!("expected {} arguments, got {}", count, args.len()))
+}
+
+// The argument at index, of count arguments, as each kind of value
+fn argument(args: &[Value], index: usize, count: usize) -> io::Result<&Value> {
+    if args.len() != count {
+        return Err(arity(count, args));
+    }
+    Ok(&args[index])
+}
+
+fn number(args: &[Value], index: usize, count: usize) -> io::Result<f32> {
+    match argument(args, index, count)? {
+        Value::Number(number) => Ok(*number),
+        arg => Err(invalid(&format!("{} is not a number", arg))),
+    }
+}
+
+fn vector(args: &[Value], index: usize, count: usize) -> io::Result<Vector> {
+    match argument(args, index, count)? {
+        Value::Vector(v) => Ok(*v),
+        arg => Err(invalid(&format!("{} is not a vector", arg))),
+    }
+}
+
+fn segment(args: &[Value], index: usize, count: usize) -> io::Result<Segment> {
+    match argument(args, index, count)? {
+        Value::Segment(s) => Ok(*s),
+        arg => Err(invalid(&format!("{} is not a segment", arg))),
+    }
+}
+
+fn painter(args: &[Value], index: usize, count: usize) -> io::Result<Paint> {
+    match argument(args, index, count)? {
+        Value::Painter(paint) => Ok(paint.clone()),
+        arg => Err(invalid(&format!("{} is not a painter", arg))),
+    }
+}
+
+fn segments_painter(segments: Vec<Segment>) -> Value {
+    Value::Painter(Rc::new(move |frame| {
+        let mut painter = Painter::new(*frame);
+        painter.render_lines(&segments, &Style::stroke(1.0, Colour::BLACK));
+        painter.into()
+    }))
+}
+
+fn path_painter(points: Vec<Vector>) -> Value {
+    Value::Painter(Rc::new(move |frame| {
+        let mut painter = Painter::new(*frame);
+        painter.render_path(&points, true, &Style::stroke(1.0, Colour::BLACK));
+        painter.into()
+    }))
+}
+
+// The crate's combinators lay out frames, so each is given a single empty painter in the
+// frame, and the painter being transformed then draws into every frame of the result
+fn single(paint: Paint, layout: fn(&Painter) -> Painter) -> io::Result<Value> {
+    Ok(Value::Painter(Rc::new(move |frame| paint(&layout(&Painter::new(*frame)).frame))))
+}
+
+fn pair(first: Paint, second: Paint, layout: fn(&CompositePainter, &CompositePainter) -> CompositePainter)
+        -> io::Result<Value> {
+    Ok(Value::Painter(Rc::new(move |frame| {
+        let unit: CompositePainter = Painter::new(*frame).into();
+        let laid_out = layout(&unit, &unit);
+        let [p1, p2] = laid_out.painters() else { unreachable!("a combinator of two painters gives two") };
+        first(&p1.frame).join(&second(&p2.frame)).with_frame(*frame)
+    })))
+}
+
+fn split(args: &[Value], layout: fn(&CompositePainter, usize) -> CompositePainter) -> io::Result<Value> {
+    let paint = painter(args, 0, 2)?;
+    let n = number(args, 1, 2)?;
+    if n < 0.0 || n.fract() != 0.0 {
+        return Err(invalid(&format!("{} is not a depth", n)));
+    }
+    Ok(Value::Painter(Rc::new(move |frame| {
+        let laid_out = layout(&Painter::new(*frame).into(), n as usize);
+        laid_out.painters().iter()
+            .fold(CompositePainter::new(vec![]).with_frame(*frame), |painted, p| painted.join(&paint(&p.frame)))
+    })))
+}
+
+fn bad_form(expr: &Expr) -> io::Error {
+    invalid(&format!("bad syntax {}", expr))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::bounds::Bounds;
+
+    fn run(source: &str) -> io::Result<Value> {
+        Interpreter::new().run(source)
+    }
+
+    fn number(source: &str) -> f32 {
+        match run(source).unwrap() {
+            Value::Number(number) => number,
+            value => panic!("{}", value),
+        }
+    }
+
+    fn frame() -> Frame {
+        Frame::new(Vector::zero(), Vector::new(160.0, 0.0), Vector::new(0.0, 160.0))
+    }
+
+    fn frames(composite: &CompositePainter) -> Vec<Frame> {
+        composite.painters().iter().map(|p| p.frame).collect()
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(number("(+ 1 (* 2 3) (- 4) (/ 1 2))"), 3.5);
+        assert_eq!(number("(- 10 1 2)"), 7.0);
+        assert_eq!(run("(< 1 2 3)").unwrap().to_string(), "#t");
+        assert_eq!(run("(not (= 1 1))").unwrap().to_string(), "#f");
+    }
+
+    #[test]
+    fn define_and_lambda() {
+        assert_eq!(number("(define (fact n) (if (= n 0) 1 (* n (fact (- n 1))))) (fact 5)"), 120.0);
+        assert_eq!(number("(define add1 (lambda (x) (+ x 1))) (add1 2)"), 3.0);
+        assert_eq!(number("(define (adder n) (lambda (x) (+ x n))) ((adder 3) 4)"), 7.0);
+        assert_eq!(number("(let ((x 2) (y 3)) (begin (* x y)))"), 6.0);
+    }
+
+    #[test]
+    fn definitions_last_between_runs() {
+        let mut interpreter = Interpreter::new();
+        interpreter.run("(define x 2)").unwrap();
+        assert_eq!(interpreter.run("(+ x 1)").unwrap().to_string(), "3");
+    }
+
+    #[test]
+    fn vectors_and_segments() {
+        assert_eq!(number("(xcor-vect (add-vect (make-vect 1 2) (scale-vect 2 (make-vect 3 4))))"), 7.0);
+        assert_eq!(run("(end-segment (make-segment (make-vect 0 0) (make-vect 1 0.5)))").unwrap().to_string(),
+                   "#<vect 1 0.5>");
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| run(source).unwrap_err().to_string();
+        assert_eq!(error("wave2"), "unbound variable wave2");
+        assert_eq!(error("(define (f x) x) (f 1 2)"), "in f: expected 1 arguments, got 2");
+        assert_eq!(error("(beside wave 1)"), "in beside: 1 is not a painter");
+        assert_eq!(error("(1 2)"), "in 1: 1 is not a procedure");
+        assert_eq!(error("(if)"), "bad syntax (if)");
+        assert_eq!(error("(right-split wave 1.5)"), "in right-split: 1.5 is not a depth");
+        assert_eq!(Interpreter::new().paint("1", frame()).unwrap_err().to_string(),
+                   "a picture must end with a painter, not 1");
+    }
+
+    #[test]
+    fn beside_and_flip() {
+        let painted = Interpreter::new().paint("(beside wave (flip-vert wave))", frame()).unwrap();
+        assert_eq!(painted.frame(), frame());
+        assert_eq!(frames(&painted), vec![
+            Frame::new(Vector::zero(), Vector::new(80.0, 0.0), Vector::new(0.0, 160.0)),
+            Frame::new(Vector::new(80.0, 160.0), Vector::new(80.0, 0.0), Vector::new(0.0, -160.0)),
+        ]);
+        assert!(painted.painters().iter().all(|p| p.shapes().len() == picture::wave().len()));
+    }
+
+    #[test]
+    fn square_of_four() {
+        let painted = Interpreter::new().paint("((square-of-four identity identity identity identity) cross)", frame())
+            .unwrap();
+        assert_eq!(painted.frame(), frame());
+        assert_eq!(frames(&painted), vec![
+            Frame::new(Vector::zero(), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(80.0, 0.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(0.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(80.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+        ]);
+    }
+
+    #[test]
+    fn square_limit() {
+        // A corner split of depth 1 is six painters, so each quarter is six frames
+        let painted = Interpreter::new().paint("(square-limit wave 1)", frame()).unwrap();
+        assert_eq!(painted.frame(), frame());
+        let frames = frames(&painted);
+        assert_eq!(frames.len(), 24);
+        // Bottom left turned half way round, bottom right flipped upside down, top left flipped
+        // left to right and top right as it is, each with its largest painter in the corner
+        assert_eq!(frames[0], Frame::new(Vector::new(80.0, 80.0), Vector::new(-40.0, 0.0), Vector::new(0.0, -40.0)));
+        assert_eq!(frames[6], Frame::new(Vector::new(80.0, 80.0), Vector::new(40.0, 0.0), Vector::new(0.0, -40.0)));
+        assert_eq!(frames[12], Frame::new(Vector::new(80.0, 80.0), Vector::new(-40.0, 0.0), Vector::new(0.0, 40.0)));
+        assert_eq!(frames[18], Frame::new(Vector::new(80.0, 80.0), Vector::new(40.0, 0.0), Vector::new(0.0, 40.0)));
+        let quarters: Vec<Bounds> = frames.chunks(6)
+            .map(|quarter| Bounds::union_all(quarter.iter().map(Frame::bounds)).unwrap())
+            .collect();
+        assert_eq!(quarters, vec![
+            Bounds::new(Vector::zero(), Vector::new(80.0, 80.0)),
+            Bounds::new(Vector::new(80.0, 0.0), Vector::new(160.0, 80.0)),
+            Bounds::new(Vector::new(0.0, 80.0), Vector::new(80.0, 160.0)),
+            Bounds::new(Vector::new(80.0, 80.0), Vector::new(160.0, 160.0)),
+        ]);
+    }
+
+    #[test]
+    fn painters_from_segments() {
+        let source = "
+            (define x-painter
+              (segments->painter (list (make-segment (make-vect 0 0) (make-vect 1 1))
+                                       (make-segment (make-vect 1 0) (make-vect 0 1)))))
+            (transform-painter x-painter (make-vect 0.5 0.5) (make-vect 1 0.5) (make-vect 0.5 1))";
+        let mut painted = Interpreter::new().paint(source, frame()).unwrap();
+        let shapes = painted.paint();
+        assert_eq!(shapes.len(), 2);
+        assert_eq!(shapes[0].bounds().map(|b| (b.min, b.max)),
+                   Some((Vector::new(79.5, 79.5), Vector::new(160.5, 160.5))));
+    }
+
+    #[test]
+    fn user_defined_painter_abstractions() {
+        let source = "(define (four painter) (flipped-pairs (beside painter painter))) (four diamond)";
+        assert_eq!(Interpreter::new().paint(source, frame()).unwrap().painters().len(), 8);
+    }
+}
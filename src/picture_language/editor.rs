@@ -0,0 +1,378 @@
+use std::io;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::picture_file;
+use crate::picture_language::segment::Segment;
+use crate::picture_language::sexpr::{self, Expr};
+use crate::picture_language::shape::Shape;
+use crate::picture_language::style::Style;
+use crate::picture_language::vector::Vector;
+
+// Pixels left around the unit square in the window
+const MARGIN: f32 = 40.0;
+// How close, in pixels, the pointer must be to a segment to select it
+const SELECT_DISTANCE: f32 = 6.0;
+
+// Draws segments into the unit square for use as a tile. Clicks go to the nearest grid point
+// and draw a path from one to the next, until the path is finished or the last point is
+// clicked again. The square has y up, as in SICP, so a tile comes out the way it was drawn
+// when it is painted into the book's frames.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Editor {
+    size: Vector,
+    grid: usize, // divisions along each side of the square
+    drawing: Drawing,
+    selected: Option<usize>,
+    cursor: Option<Vector>, // in the window
+    undone: Vec<Drawing>,
+    redone: Vec<Drawing>,
+}
+
+// What undo and redo go back and forth between
+#[derive(Debug, PartialEq, Clone)]
+struct Drawing {
+    segments: Vec<Segment>,
+    pen: Option<Vector>, // the end of the path being drawn
+}
+
+impl Editor {
+    // The tile saved at the path, or a new one if there is no file yet
+    pub fn open(path: &str, width: f32, height: f32) -> io::Result<Self> {
+        let editor = Editor::new(width, height);
+        match picture_file::read_file::<Vec<Segment>>(path) {
+            Ok(segments) => Ok(editor.with_segments(segments)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(editor),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn new(width: f32, height: f32) -> Self {
+        Editor {
+            size: Vector::new(width, height),
+            grid: 10,
+            drawing: Drawing { segments: vec![], pen: None },
+            selected: None,
+            cursor: None,
+            undone: vec![],
+            redone: vec![],
+        }
+    }
+
+    pub fn with_grid(self, divisions: usize) -> Self {
+        Editor { grid: divisions.max(1), ..self }
+    }
+
+    pub fn with_segments(self, segments: Vec<Segment>) -> Self {
+        Editor { drawing: Drawing { segments, pen: None }, ..self }
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.size = Vector::new(width, height);
+    }
+
+    // The unit square, as large as the window allows and centred in it
+    pub fn frame(&self) -> Frame {
+        let side = (self.size.x.min(self.size.y) - 2.0 * MARGIN).max(1.0);
+        let origin = self.size.scale(0.5) + Vector::new(-side, side).scale(0.5);
+        Frame::new(origin, Vector::new(side, 0.0), Vector::new(0.0, -side))
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.drawing.segments
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn is_drawing(&self) -> bool {
+        self.drawing.pen.is_some()
+    }
+
+    // The grid point of the unit square nearest the window point
+    pub fn snap(&self, window_point: Vector) -> Vector {
+        let divisions = self.grid as f32;
+        let snap = |t: f32| ((t * divisions).round() / divisions).clamp(0.0, 1.0);
+        // The square is always at least a pixel across
+        let unit = self.frame().unmap(window_point).unwrap_or(Vector::zero());
+        Vector::new(snap(unit.x), snap(unit.y))
+    }
+
+    pub fn move_to(&mut self, window_point: Vector) {
+        self.cursor = Some(window_point);
+    }
+
+    // Starts a path, continues it, or finishes it when its last point is clicked again
+    pub fn click(&mut self, window_point: Vector) {
+        let point = self.snap(window_point);
+        match self.drawing.pen {
+            Some(pen) if pen == point => self.finish_path(),
+            Some(pen) => self.change(|drawing| {
+                drawing.segments.push(Segment::new(pen, point));
+                drawing.pen = Some(point);
+            }),
+            None => self.change(|drawing| drawing.pen = Some(point)),
+        }
+    }
+
+    // Undo picks the path up again from its last point
+    pub fn finish_path(&mut self) {
+        self.drawing.pen = None;
+    }
+
+    // The segment nearest the window point, if it is close enough; anything else is deselected
+    pub fn select_at(&mut self, window_point: Vector) -> Option<usize> {
+        let frame = self.frame();
+        self.selected = self.drawing.segments.iter()
+            .map(|segment| frame.map_segment(*segment).distance_to(window_point))
+            .enumerate()
+            .filter(|(_, distance)| *distance <= SELECT_DISTANCE)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(index, _)| index);
+        self.selected
+    }
+
+    pub fn delete_selected(&mut self) -> bool {
+        match self.selected.take() {
+            Some(index) => {
+                self.change(|drawing| {
+                    drawing.segments.remove(index);
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(drawing) => {
+                self.redone.push(std::mem::replace(&mut self.drawing, drawing));
+                self.selected = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redone.pop() {
+            Some(drawing) => {
+                self.undone.push(std::mem::replace(&mut self.drawing, drawing));
+                self.selected = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn change(&mut self, edit: impl FnOnce(&mut Drawing)) {
+        self.undone.push(self.drawing.clone());
+        self.redone.clear();
+        edit(&mut self.drawing);
+    }
+
+    // The grid, the segments with the selected one highlighted, and the path's next segment
+    // following the pointer
+    pub fn shapes(&self) -> Vec<Shape> {
+        let frame = self.frame();
+        let grid_style = Style::stroke(1.0, Colour::from_rgb(0.85, 0.85, 0.85));
+        let mut shapes: Vec<Shape> = (0..=self.grid).flat_map(|i| {
+            let t = i as f32 / self.grid as f32;
+            [Segment::new(Vector::new(t, 0.0), Vector::new(t, 1.0)),
+             Segment::new(Vector::new(0.0, t), Vector::new(1.0, t))]
+        }).map(|line| Shape::new_line(frame.map_segment(line), grid_style.clone())).collect();
+        let corners = [Vector::zero(), Vector::new(1.0, 0.0), Vector::one(), Vector::new(0.0, 1.0)];
+        shapes.push(Shape::new_polygon(corners.iter().map(|corner| frame.map(*corner)).collect(),
+                                       Style::stroke(1.0, Colour::GRAY)));
+        for (index, segment) in self.drawing.segments.iter().enumerate() {
+            let style = if self.selected == Some(index) {
+                Style::stroke(4.0, Colour::RED)
+            } else {
+                Style::stroke(2.0, Colour::BLACK)
+            };
+            shapes.push(Shape::new_line(frame.map_segment(*segment), style));
+        }
+        if let Some(pen) = self.drawing.pen {
+            let pen = frame.map(pen);
+            shapes.push(Shape::new_circle(&pen, 4.0, Style::fill(Colour::BLUE)));
+            if let Some(cursor) = self.cursor {
+                let next = frame.map(self.snap(cursor));
+                let style = Style::stroke(1.0, Colour::BLUE).with_dash(vec![4.0, 4.0]);
+                shapes.push(Shape::new_line(Segment::new(pen, next), style));
+            }
+        }
+        shapes
+    }
+
+    // Saved as a picture file of segments, like the tiles next to picture.rs
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        picture_file::write_file(path, &self.drawing.segments)
+    }
+
+    // A painter for the picture language from the tile saved at the path, such as
+    //
+    //   (define boat (segments->painter (load-segments "tiles/boat.sexp")))
+    //
+    // or None if the name would not read back as a symbol
+    pub fn definition(name: &str, path: &str) -> Option<Expr> {
+        if !matches!(sexpr::parse(name), Ok(Expr::Symbol(symbol)) if symbol == name) {
+            return None;
+        }
+        let segments = Expr::list(vec![Expr::symbol("load-segments"), Expr::Str(path.to_string())]);
+        Some(Expr::list(vec![
+            Expr::symbol("define"),
+            Expr::symbol(name),
+            Expr::list(vec![Expr::symbol("segments->painter"), segments]),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::dsl::Interpreter;
+
+    // A 100 pixel square with its origin at (40, 150), and a grid of tenths
+    fn editor() -> Editor {
+        Editor::new(180.0, 200.0)
+    }
+
+    fn window_point(x: f32, y: f32) -> Vector {
+        editor().frame().map(Vector::new(x, y))
+    }
+
+    #[test]
+    fn frame() {
+        let frame = editor().frame();
+        assert_eq!(frame.origin(), Vector::new(40.0, 150.0));
+        assert_eq!(frame.map(Vector::one()), Vector::new(140.0, 50.0));
+    }
+
+    #[test]
+    fn snap() {
+        let editor = editor().with_grid(4);
+        assert_eq!(editor.snap(Vector::new(66.0, 124.0)), Vector::new(0.25, 0.25));
+        assert_eq!(editor.snap(Vector::new(0.0, 300.0)), Vector::zero());
+        assert_eq!(editor.snap(Vector::new(138.0, 52.0)), Vector::one());
+    }
+
+    #[test]
+    fn draw_paths() {
+        let mut editor = editor();
+        editor.click(window_point(0.0, 0.0));
+        assert!(editor.is_drawing());
+        assert!(editor.segments().is_empty());
+        editor.click(window_point(0.51, 0.49));
+        editor.click(window_point(1.0, 0.0));
+        editor.click(window_point(1.0, 0.0));
+        assert!(!editor.is_drawing());
+        editor.click(window_point(0.0, 1.0));
+        editor.click(window_point(1.0, 1.0));
+        editor.finish_path();
+        assert_eq!(editor.segments(), &[
+            Segment::new(Vector::zero(), Vector::new(0.5, 0.5)),
+            Segment::new(Vector::new(0.5, 0.5), Vector::new(1.0, 0.0)),
+            Segment::new(Vector::new(0.0, 1.0), Vector::one()),
+        ]);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut editor = editor();
+        editor.click(window_point(0.0, 0.0));
+        editor.click(window_point(1.0, 1.0));
+        editor.click(window_point(1.0, 0.0));
+        let drawn = editor.clone();
+        assert!(editor.undo());
+        assert_eq!(editor.segments(), &[Segment::new(Vector::zero(), Vector::one())]);
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert!(!editor.is_drawing());
+        assert!(!editor.undo());
+        assert!(editor.redo());
+        assert!(editor.redo());
+        assert!(editor.redo());
+        assert!(!editor.redo());
+        assert_eq!(editor.segments(), drawn.segments());
+
+        // A new edit drops what was undone
+        editor.undo();
+        editor.click(window_point(0.0, 1.0));
+        assert!(!editor.redo());
+        assert_eq!(editor.segments()[1], Segment::new(Vector::one(), Vector::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn select_and_delete() {
+        let across = Segment::new(Vector::new(0.0, 0.5), Vector::new(1.0, 0.5));
+        let up = Segment::new(Vector::new(0.2, 0.0), Vector::new(0.2, 1.0));
+        let mut editor = editor().with_segments(vec![across, up]);
+        assert_eq!(editor.select_at(window_point(0.8, 0.52)), Some(0));
+        assert_eq!(editor.select_at(window_point(0.21, 0.3)), Some(1));
+        assert!(editor.delete_selected());
+        assert_eq!(editor.segments(), &[across]);
+        assert!(!editor.delete_selected());
+        assert_eq!(editor.select_at(window_point(0.7, 0.8)), None);
+        assert!(editor.undo());
+        assert_eq!(editor.segments(), &[across, up]);
+    }
+
+    #[test]
+    fn shapes() {
+        let mut editor = editor().with_grid(2);
+        editor.click(window_point(0.0, 0.0));
+        editor.click(window_point(1.0, 1.0));
+        editor.move_to(window_point(1.0, 0.0));
+        // Six grid lines and the square, the segment, the pen and the segment it would draw next
+        let shapes = editor.shapes();
+        assert_eq!(shapes.len(), 10);
+        assert_eq!(shapes[7], Shape::new_line(Segment::new(Vector::new(40.0, 150.0), Vector::new(140.0, 50.0)),
+                                              Style::stroke(2.0, Colour::BLACK)));
+        assert_eq!(shapes[9], Shape::new_line(Segment::new(Vector::new(140.0, 50.0), Vector::new(140.0, 150.0)),
+                                              Style::stroke(1.0, Colour::BLUE).with_dash(vec![4.0, 4.0])));
+    }
+
+    #[test]
+    fn save_and_open() {
+        let segments = vec![
+            Segment::new(Vector::zero(), Vector::new(0.5, 1.0)),
+            Segment::new(Vector::new(0.5, 1.0), Vector::new(1.0, 0.0)),
+        ];
+        let path = std::env::temp_dir().join(format!("sicp-tile-{}.sexp", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        assert!(Editor::open(path, 180.0, 200.0).unwrap().segments().is_empty());
+        editor().with_segments(segments.clone()).save(path).unwrap();
+        assert_eq!(picture_file::read_file::<Vec<Segment>>(path).unwrap(), segments);
+        let mut reopened = Editor::open(path, 180.0, 200.0).unwrap();
+        assert_eq!(reopened.segments(), &segments[..]);
+
+        // Drawing more keeps what was there
+        reopened.click(window_point(0.0, 0.0));
+        reopened.click(window_point(1.0, 0.0));
+        reopened.save(path).unwrap();
+        assert_eq!(Editor::open(path, 180.0, 200.0).unwrap().segments().len(), 3);
+
+        // The definition paints the saved segments into whatever frame it is given
+        let definition = Editor::definition("peak", path).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.run(&definition.pretty()).unwrap();
+        let frame = Frame::new(Vector::zero(), Vector::new(10.0, 0.0), Vector::new(0.0, 10.0));
+        assert_eq!(interpreter.paint("peak", frame).unwrap().paint().len(), 3);
+        std::fs::remove_file(path).unwrap();
+
+        std::fs::write(path, "(not a tile)").unwrap();
+        assert!(Editor::open(path, 180.0, 200.0).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn definition() {
+        assert_eq!(Editor::definition("boat", "tiles/boat.sexp").unwrap().to_string(),
+                   "(define boat (segments->painter (load-segments \"tiles/boat.sexp\")))");
+        assert_eq!(Editor::definition("my boat", "tiles/my boat.sexp"), None);
+        assert_eq!(Editor::definition("2", "tiles/2.sexp"), None);
+        assert_eq!(Editor::definition("(boat)", "tiles/(boat).sexp"), None);
+    }
+}
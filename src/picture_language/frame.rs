@@ -1,4 +1,6 @@
+use crate::picture_language::bounds::Bounds;
 use crate::picture_language::segment::Segment;
+use crate::picture_language::transform::Transform;
 use crate::picture_language::vector::Vector;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -13,16 +15,53 @@ impl Frame {
         Frame { origin, edge1, edge2 }
     }
 
+    pub fn origin(&self) -> Vector {
+        self.origin
+    }
+
+    pub fn edge1(&self) -> Vector {
+        self.edge1
+    }
+
+    pub fn edge2(&self) -> Vector {
+        self.edge2
+    }
+
     // v is within unit square
     pub fn map(&self, v: Vector) -> Vector {
         self.origin + self.edge1.scale(v.x) + self.edge2.scale(v.y)
     }
 
+    // The inverse of map, taking v back into this frame's unit square. A flat frame has no inverse.
+    pub fn unmap(&self, v: Vector) -> Option<Vector> {
+        Some(Transform::from(*self).inverse()?.apply_vector(v - self.origin))
+    }
+
+    // f is a frame within unit square
+    pub fn map_frame(&self, f: &Frame) -> Frame {
+        self.transform_painter(f.origin, f.origin + f.edge1, f.origin + f.edge2)
+    }
+
+    pub fn unmap_frame(&self, f: &Frame) -> Option<Frame> {
+        let origin = self.unmap(f.origin)?;
+        Some(Frame::new(origin, self.unmap(f.origin + f.edge1)? - origin, self.unmap(f.origin + f.edge2)? - origin))
+    }
+
     // s is within unit square
     pub fn map_segment(&self, s: Segment) -> Segment {
         Segment::new(self.map(s.start), self.map(s.end))
     }
 
+    // The part of this frame from (x0, y0) to (x1, y1) in its unit square
+    pub fn subframe(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        self.transform_painter(Vector::new(x0, y0), Vector::new(x1, y0), Vector::new(x0, y1))
+    }
+
+    // This frame moved by a transform of the device space it lies in
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        Transform::from(*self).then(transform).into()
+    }
+
     pub fn below(&self) -> Self {
         Frame::new(self.origin + Vector::new(self.edge2.x, self.edge2.y), self.edge1, self.edge2)
     }
@@ -31,17 +70,27 @@ impl Frame {
         Frame::new(self.origin + Vector::new(self.edge1.x, self.edge1.y), self.edge1, self.edge2)
     }
 
-    pub fn x_max(&self) -> f32 {
-        self.origin.x.max(self.origin.x + self.edge1.x).max(self.origin.x + self.edge2.x)
+    // How much the frame stretches unit-square lengths: the square root of its area, so that
+    // a skewed or unevenly scaled frame still has a single scale
+    pub fn scale(&self) -> f32 {
+        (self.edge1.x * self.edge2.y - self.edge1.y * self.edge2.x).abs().sqrt()
     }
 
-    pub fn y_max(&self) -> f32 {
-        self.origin.y.max(self.origin.y + self.edge1.y).max(self.origin.y + self.edge2.y)
+    pub fn bounds(&self) -> Bounds {
+        Bounds::from_points(&[self.origin, self.origin + self.edge1, self.origin + self.edge2,
+            self.origin + self.edge1 + self.edge2]).unwrap()
     }
 
     pub fn transform(&self, v: Vector) -> Self {
         Frame::new(self.origin + v, self.edge1, self.edge2)
     }
+
+    // As SICP's transform-painter: origin, corner1 and corner2 are within the unit square,
+    // and give where this frame's origin and the ends of its two edges move to
+    pub fn transform_painter(&self, origin: Vector, corner1: Vector, corner2: Vector) -> Self {
+        let new_origin = self.map(origin);
+        Frame::new(new_origin, self.map(corner1) - new_origin, self.map(corner2) - new_origin)
+    }
 }
 
 #[cfg(test)]
@@ -67,4 +116,92 @@ mod tests {
         assert_eq!(f.map(Vector::new(1.0, 1.0)), Vector::new(110.0, 220.0));
         assert_eq!(f.map(Vector::new(0.5, 0.5)), Vector::new(60.0, 120.0));
     }
+
+    #[test]
+    fn scale() {
+        assert_eq!(Frame::new(Vector::one(), Vector::new(100.0, 0.0), Vector::new(0.0, 100.0)).scale(), 100.0);
+        assert_eq!(Frame::new(Vector::one(), Vector::new(0.0, 4.0), Vector::new(9.0, 9.0)).scale(), 6.0);
+    }
+
+    #[test]
+    fn bounds() {
+        let f = Frame::new(Vector::new(10.0, 20.0), Vector::new(100.0, -50.0), Vector::new(-20.0, 200.0));
+        assert_eq!(f.bounds(), Bounds::new(Vector::new(-10.0, -30.0), Vector::new(110.0, 220.0)));
+    }
+
+    #[test]
+    fn unmap() {
+        let f = Frame::new(Vector::new(10.0, 20.0),
+                           Vector::new(100.0, 50.0),
+                           Vector::new(-20.0, 200.0));
+        assert_eq!(f.unmap(Vector::new(10.0, 20.0)), Some(Vector::zero()));
+        assert_eq!(f.unmap(f.map(Vector::new(0.5, 0.25))), Some(Vector::new(0.5, 0.25)));
+        assert_eq!(f.unmap(Vector::new(90.0, 270.0)), Some(Vector::one()));
+        let flat = Frame::new(Vector::zero(), Vector::new(1.0, 2.0), Vector::new(2.0, 4.0));
+        assert_eq!(flat.unmap(Vector::one()), None);
+        assert_eq!(flat.unmap_frame(&f), None);
+    }
+
+    #[test]
+    fn map_frame_undoes_unmap_frame() {
+        let outer = Frame::new(Vector::new(10.0, 20.0),
+                               Vector::new(100.0, 0.0),
+                               Vector::new(0.0, 200.0));
+        let inner = Frame::new(Vector::new(60.0, 120.0),
+                               Vector::new(50.0, 0.0),
+                               Vector::new(0.0, -100.0));
+        let unit = outer.unmap_frame(&inner).unwrap();
+        assert_eq!(unit, Frame::new(Vector::new(0.5, 0.5), Vector::new(0.5, 0.0), Vector::new(0.0, -0.5)));
+        assert_eq!(outer.map_frame(&unit), inner);
+    }
+
+    #[test]
+    fn transform_painter() {
+        let f = Frame::new(Vector::new(10.0, 20.0),
+                           Vector::new(100.0, 0.0),
+                           Vector::new(0.0, 200.0));
+        let right_half = f.transform_painter(Vector::new(0.5, 0.0),
+                                             Vector::new(1.0, 0.0),
+                                             Vector::new(0.5, 1.0));
+        assert_eq!(right_half, Frame::new(Vector::new(60.0, 20.0),
+                                          Vector::new(50.0, 0.0),
+                                          Vector::new(0.0, 200.0)));
+    }
+
+    #[test]
+    fn subframe() {
+        let f = Frame::new(Vector::new(10.0, 20.0),
+                           Vector::new(100.0, 0.0),
+                           Vector::new(0.0, 200.0));
+        let quarter = f.subframe(0.5, 0.5, 1.0, 1.0);
+        assert_eq!(quarter, Frame::new(Vector::new(60.0, 120.0),
+                                       Vector::new(50.0, 0.0),
+                                       Vector::new(0.0, 100.0)));
+        assert_eq!(f.unmap(quarter.map(Vector::zero())), Some(Vector::new(0.5, 0.5)));
+    }
+
+    #[test]
+    fn transformed() {
+        let f = Frame::new(Vector::new(10.0, 20.0),
+                           Vector::new(100.0, 0.0),
+                           Vector::new(0.0, 200.0));
+        let moved = f.transformed(&Transform::scale(2.0, 1.0).then(&Transform::translate(Vector::new(0.0, 5.0))));
+        assert_eq!(moved, Frame::new(Vector::new(20.0, 25.0),
+                                     Vector::new(200.0, 0.0),
+                                     Vector::new(0.0, 200.0)));
+    }
+
+    #[test]
+    fn transform_painter_flips() {
+        let f = Frame::new(Vector::zero(),
+                           Vector::new(100.0, 0.0),
+                           Vector::new(0.0, 200.0));
+        let flipped = f.transform_painter(Vector::new(0.0, 1.0),
+                                          Vector::new(1.0, 1.0),
+                                          Vector::zero());
+        assert_eq!(flipped, Frame::new(Vector::new(0.0, 200.0),
+                                       Vector::new(100.0, 0.0),
+                                       Vector::new(0.0, -200.0)));
+        assert_eq!(flipped.map(Vector::new(0.25, 0.25)), Vector::new(25.0, 150.0));
+    }
 }
\ No newline at end of file
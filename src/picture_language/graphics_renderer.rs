@@ -0,0 +1,104 @@
+use speedy2d::dimen::Vec2;
+use speedy2d::shape::Polygon;
+use std::cell::RefCell;
+use std::collections::hash_map::{Entry, HashMap};
+use speedy2d::font::{Font, TextLayout, TextOptions};
+use speedy2d::image::{ImageDataType, ImageHandle, ImageSmoothingMode};
+use speedy2d::Graphics2D;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
+use crate::picture_language::renderer::{self, Renderer};
+use crate::picture_language::segment::Segment;
+use crate::picture_language::bitmap_font;
+use crate::picture_language::shape::{ellipse_points, Anchor};
+use crate::picture_language::style::{LineCap, Style};
+use crate::picture_language::vector::Vector;
+
+// Text uses the TrueType font at the path in SICP_FONT, or else the first of these that exists.
+// Without any, it is drawn in the built-in bitmap font.
+const FONT_PATHS: [&str; 3] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+thread_local! {
+    static FONT: Option<Font> = load_font();
+    // Textures, by image id, so each image is only uploaded once
+    static TEXTURES: RefCell<HashMap<u64, ImageHandle>> = RefCell::new(HashMap::new());
+}
+
+fn load_font() -> Option<Font> {
+    std::env::var("SICP_FONT").ok().into_iter()
+        .chain(FONT_PATHS.iter().map(|path| path.to_string()))
+        .find_map(|path| std::fs::read(path).ok().and_then(|bytes| Font::new(&bytes).ok()))
+}
+
+pub fn to_vec2(vec: &Vector) -> Vec2 {
+    Vec2 { x: vec.x, y: vec.y }
+}
+
+impl Renderer for Graphics2D {
+    fn clear(&mut self, colour: Colour) {
+        self.clear_screen(colour.into());
+    }
+
+    // speedy2d's own lines are butt-ended and solid
+    fn draw_line(&mut self, segment: &Segment, style: &Style) {
+        match style.stroke_colour() {
+            Some(colour) if style.cap == LineCap::Butt && style.dash_pattern().is_none() =>
+                Graphics2D::draw_line(self, to_vec2(&segment.start), to_vec2(&segment.end),
+                                      style.width, colour.into()),
+            _ => self.draw_polyline(&[segment.start, segment.end], false, style),
+        }
+    }
+
+    fn draw_polygon(&mut self, points: &[Vector], style: &Style) {
+        if let Some(colour) = style.fill_colour() {
+            let vertices: Vec<Vec2> = points.iter().map(to_vec2).collect();
+            Graphics2D::draw_polygon(self, &Polygon::new(&vertices), Vec2::new(0.0, 0.0), colour.into());
+        }
+        renderer::stroke_outline(self, points, style);
+    }
+
+    fn draw_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        if let Some(colour) = style.fill_colour() {
+            Graphics2D::draw_circle(self, to_vec2(centre), radius, colour.into());
+        }
+        let outline = ellipse_points(centre, Vector::new(radius, 0.0), Vector::new(0.0, radius));
+        renderer::stroke_outline(self, &outline, style);
+    }
+
+    // Texture mapped onto the frame's parallelogram, tinted to fade it
+    fn draw_image(&mut self, image: &Image, frame: &Frame, opacity: f32) {
+        let texture = TEXTURES.with(|textures| match textures.borrow_mut().entry(image.id()) {
+            Entry::Occupied(entry) => Some(entry.get().clone()),
+            Entry::Vacant(entry) => self
+                .create_image_from_raw_pixels(ImageDataType::RGBA, ImageSmoothingMode::Linear,
+                                              (image.width(), image.height()), &image.to_rgba())
+                .ok().map(|handle| entry.insert(handle).clone()),
+        });
+        match texture {
+            Some(texture) => {
+                let unit = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)];
+                let corners = unit.map(|corner| to_vec2(&frame.map(Vector::new(corner.x, corner.y))));
+                let tint = Colour::from_rgba(1.0, 1.0, 1.0, opacity).into();
+                self.draw_quad_image_tinted_four_color(corners, [tint; 4], unit, &texture);
+            }
+            None => renderer::image_pixels(self, image, frame, opacity),
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        let Some(colour) = style.text_colour() else { return };
+        let block = FONT.with(|font| font.as_ref().map(|font| font.layout_text(text, size, TextOptions::new())));
+        match block {
+            Some(block) => {
+                let corner = anchor.top_left(position, &Vector::new(block.width(), block.height()));
+                Graphics2D::draw_text(self, to_vec2(&corner), colour.into(), &block);
+            }
+            None => bitmap_font::draw_text(self, text, position, size, anchor, style),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::picture_language::colour::Colour;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// A bitmap for image painters, as straight (not premultiplied) RGBA rows from the top.
+// Each image gets its own id, so backends can keep a texture for it between frames.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Image {
+    id: u64,
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize, "an image needs width * height pixels");
+        Image { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), width, height, pixels }
+    }
+
+    // As new, but decoded pixels that don't fill the image are an error rather than a panic
+    fn decoded(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> io::Result<Self> {
+        if pixels.len() != width as usize * height as usize {
+            return Err(invalid("image data does not match its size"));
+        }
+        Ok(Image::new(width, height, pixels))
+    }
+
+    // PNG or PPM (P3 or P6), whatever the file's extension
+    pub fn load(path: &str) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(b"\x89PNG") {
+            Image::decode_png(&bytes[..])
+        } else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+            Image::decode_ppm(&bytes)
+        } else {
+            Err(invalid(&format!("{} is not a PNG or PPM image", path)))
+        }
+    }
+
+    pub fn decode_png<R: Read>(r: R) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(r);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let samples = &buffer[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Grayscale => samples.iter().map(|&v| [v, v, v, 255]).collect(),
+            png::ColorType::GrayscaleAlpha => samples.chunks(2).map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Rgb => samples.chunks(3).map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::Rgba => samples.chunks(4).map(|p| [p[0], p[1], p[2], p[3]]).collect(),
+            png::ColorType::Indexed => return Err(invalid("indexed PNG was not expanded")),
+        };
+        Image::decoded(info.width, info.height, pixels)
+    }
+
+    // Plain (P3) or raw (P6) PPM, with any maximum value up to 65535
+    pub fn decode_ppm(bytes: &[u8]) -> io::Result<Self> {
+        let mut position = 0;
+        let magic = ppm_token(bytes, &mut position)?;
+        let mut number = || ppm_token(bytes, &mut position)?.parse::<u32>()
+            .map_err(|_| invalid("PPM header is not a number"));
+        let (width, height, max) = (number()?, number()?, number()?);
+        if max == 0 || max > 65535 {
+            return Err(invalid("PPM maximum value is out of range"));
+        }
+        let count = width.checked_mul(height).and_then(|n| n.checked_mul(3))
+            .ok_or_else(|| invalid("PPM is too large"))? as usize;
+        let samples: Vec<u32> = match magic.as_str() {
+            "P3" => (0..count)
+                .map(|_| ppm_token(bytes, &mut position)?.parse().map_err(|_| invalid("PPM sample is not a number")))
+                .collect::<io::Result<_>>()?,
+            "P6" => {
+                // A single whitespace byte separates the header from the samples
+                let data = bytes.get(position + 1..).unwrap_or(&[]);
+                let size = if max < 256 { 1 } else { 2 };
+                if data.len() < count * size {
+                    return Err(invalid("PPM data is too short"));
+                }
+                data.chunks(size).take(count)
+                    .map(|sample| sample.iter().fold(0, |value, byte| value * 256 + *byte as u32))
+                    .collect()
+            }
+            _ => return Err(invalid("not a PPM image")),
+        };
+        let to_byte = |sample: u32| ((sample.min(max) * 255 + max / 2) / max) as u8;
+        let pixels = samples.chunks(3).map(|rgb| [to_byte(rgb[0]), to_byte(rgb[1]), to_byte(rgb[2]), 255]).collect();
+        Image::decoded(width, height, pixels)
+    }
+
+    pub fn encode_png<W: Write>(&self, w: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba())?;
+        Ok(())
+    }
+
+    // For embedding in text, such as SVG data URLs and saved pictures
+    pub fn to_png_base64(&self) -> String {
+        let mut png = vec![];
+        self.encode_png(&mut png).unwrap();
+        base64_encode(&png)
+    }
+
+    pub fn from_png_base64(text: &str) -> io::Result<Self> {
+        let png = base64_decode(text).ok_or_else(|| invalid("not base64"))?;
+        Image::decode_png(&png[..])
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn colour(&self, x: u32, y: u32) -> Colour {
+        let [r, g, b, a] = self.pixel(x, y);
+        Colour { a: a as f32 / 255.0, ..Colour::from_int_rgb(r, g, b) }
+    }
+
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels.concat()
+    }
+}
+
+// The next whitespace-separated word, skipping # comments
+fn ppm_token(bytes: &[u8], position: &mut usize) -> io::Result<String> {
+    loop {
+        match bytes.get(*position) {
+            Some(b'#') => while bytes.get(*position).is_some_and(|b| *b != b'\n') { *position += 1 },
+            Some(b) if b.is_ascii_whitespace() => *position += 1,
+            Some(_) => break,
+            None => return Err(invalid("PPM data is too short")),
+        }
+    }
+    let start = *position;
+    while bytes.get(*position).is_some_and(|b| !b.is_ascii_whitespace()) {
+        *position += 1;
+    }
+    Ok(String::from_utf8_lossy(&bytes[start..*position]).into_owned())
+}
+
+const BASE64_DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| bits | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_DIGITS[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// None if there is anything but digits, padding and whitespace
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u32> = text.bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b'=')
+        .map(|b| BASE64_DIGITS.iter().position(|d| *d == b).map(|d| d as u32))
+        .collect::<Option<_>>()?;
+    let mut decoded = vec![];
+    for chunk in digits.chunks(4) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, digit)| bits | digit << (18 - 6 * i));
+        for i in 0..chunk.len().saturating_sub(1) {
+            decoded.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_ppm() {
+        let image = Image::decode_ppm(b"P3\n# two by one\n2 1\n15\n15 0 0  0 15 5\n").unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(1, 0), [0, 255, 85, 255]);
+    }
+
+    #[test]
+    fn raw_ppm() {
+        let mut bytes = b"P6 1 2 255\n".to_vec();
+        bytes.extend([1, 2, 3, 4, 5, 6]);
+        let image = Image::decode_ppm(&bytes).unwrap();
+        assert_eq!(image.to_rgba(), vec![1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn bad_ppm() {
+        assert!(Image::decode_ppm(b"P6 2 2 255\n\x00\x00").is_err());
+        assert!(Image::decode_ppm(b"P3 1 1 0\n0 0 0").is_err());
+        assert!(Image::decode_ppm(b"P5 1 1 255\n0").is_err());
+    }
+
+    #[test]
+    fn oversized_ppm() {
+        let error = Image::decode_ppm(b"P6 100000 100000 255\n\x00").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "PPM is too large");
+        assert!(Image::decode_ppm(b"P3 65536 65536 255\n0").is_err());
+    }
+
+    #[test]
+    fn decoded_size() {
+        assert!(Image::decoded(2, 2, vec![[0, 0, 0, 255]; 3]).is_err());
+        assert_eq!(Image::decoded(1, 2, vec![[0, 0, 0, 255]; 2]).unwrap().height(), 2);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let image = Image::new(2, 2, vec![[255, 0, 0, 255], [0, 255, 0, 128], [0, 0, 255, 0], [10, 20, 30, 40]]);
+        let mut bytes = vec![];
+        image.encode_png(&mut bytes).unwrap();
+        let decoded = Image::decode_png(&bytes[..]).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (2, 2));
+        assert_eq!(decoded.to_rgba(), image.to_rgba());
+        assert_ne!(decoded.id(), image.id());
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_decode("Zm9v\nYmE="), Some(b"fooba".to_vec()));
+        assert_eq!(base64_decode("Zg=="), Some(b"f".to_vec()));
+        assert_eq!(base64_decode("Zm9v!"), None);
+    }
+
+    #[test]
+    fn png_base64_round_trip() {
+        let image = Image::new(1, 2, vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+        assert_eq!(Image::from_png_base64(&image.to_png_base64()).unwrap().to_rgba(), image.to_rgba());
+        assert!(Image::from_png_base64("Zm9v").is_err());
+    }
+
+    #[test]
+    fn colour() {
+        let image = Image::new(1, 1, vec![[255, 0, 51, 0]]);
+        assert_eq!(image.colour(0, 0), Colour::from_rgba(1.0, 0.0, 0.2, 0.0));
+    }
+}
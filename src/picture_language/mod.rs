@@ -1,9 +1,36 @@
 pub mod vector;
 mod frame;
+pub mod bounds;
+pub mod transform;
 mod segment;
+#[cfg(feature = "window")]
 pub mod window;
+#[cfg(feature = "window")]
 pub mod window_handler;
 pub mod shape;
 pub mod picture;
 pub mod painter;
 pub mod composite_painter;
+pub mod svg;
+pub mod raster;
+pub mod renderer;
+pub mod stroke;
+pub mod curve;
+pub mod clip;
+pub mod image;
+pub mod bitmap_font;
+#[cfg(feature = "window")]
+pub mod graphics_renderer;
+pub mod colour;
+pub mod style;
+pub mod combinators;
+pub mod patterns;
+pub mod tessellation;
+pub mod wallpaper;
+pub mod scene;
+pub mod sexpr;
+pub mod picture_file;
+pub mod dsl;
+pub mod viewport;
+pub mod coordinates;
+pub mod editor;
@@ -1,68 +1,366 @@
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::clip;
+use crate::picture_language::curve::Curve;
+use std::rc::Rc;
 use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
 use crate::picture_language::segment::Segment;
-use crate::picture_language::shape::Shape;
-use speedy2d::color::Color;
+use crate::picture_language::shape::{Anchor, Shape};
+use crate::picture_language::style::Style;
 use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::tessellation::Tessellation;
 use crate::picture_language::vector::Vector;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Painter {
     pub frame: Frame,
     shapes: Vec<Shape>,
+    clip: bool,
 }
 
 impl Painter {
     pub fn new(frame: Frame) -> Self {
-        Painter { frame, shapes: vec![] }
+        Painter { frame, shapes: vec![], clip: false }
+    }
+
+    // Cuts whatever is painted down to the frame, so it cannot spill into neighbouring tiles.
+    // Painters made from this one by the combinators clip too.
+    pub fn clipped(self) -> Self {
+        Painter { clip: true, ..self }
+    }
+
+    pub fn is_clipped(&self) -> bool {
+        self.clip
+    }
+
+    // A new painter to render into, with this one's settings
+    pub fn with_frame(&self, frame: Frame) -> Self {
+        Painter { frame, shapes: vec![], clip: self.clip }
+    }
+
+    // What has been rendered so far, in device space
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    // Such as shapes that were saved from another painter
+    pub fn with_shapes(self, shapes: Vec<Shape>) -> Self {
+        Painter { shapes, ..self }
     }
 
     pub fn below(&self) -> Self {
-        Painter { frame: self.frame.below(), shapes: vec![] }
+        self.with_frame(self.frame.below())
     }
 
     pub fn right(&self) -> Self {
-        Painter { frame: self.frame.right(), shapes: vec![] }
+        self.with_frame(self.frame.right())
+    }
+
+    // The SICP combinators act on the frame, giving a new painter to render into
+    pub fn transform_painter(&self, origin: Vector, corner1: Vector, corner2: Vector) -> Self {
+        self.with_frame(self.frame.transform_painter(origin, corner1, corner2))
+    }
+
+    pub fn flip_vert(&self) -> Self {
+        self.transform_painter(Vector::new(0.0, 1.0), Vector::new(1.0, 1.0), Vector::new(0.0, 0.0))
+    }
+
+    pub fn flip_horiz(&self) -> Self {
+        self.transform_painter(Vector::new(1.0, 0.0), Vector::new(0.0, 0.0), Vector::new(1.0, 1.0))
+    }
+
+    pub fn rotate90(&self) -> Self {
+        self.transform_painter(Vector::new(1.0, 0.0), Vector::new(1.0, 1.0), Vector::new(0.0, 0.0))
     }
 
-    pub fn render_lines(&mut self, unit_segments: &Vec<Segment>, thickness: f32, colour: Color) {
+    pub fn rotate180(&self) -> Self {
+        self.transform_painter(Vector::new(1.0, 1.0), Vector::new(0.0, 1.0), Vector::new(1.0, 0.0))
+    }
+
+    pub fn rotate270(&self) -> Self {
+        self.transform_painter(Vector::new(0.0, 1.0), Vector::new(0.0, 0.0), Vector::new(1.0, 1.0))
+    }
+
+    pub fn shrink_to_upper_right(&self) -> Self {
+        self.transform_painter(Vector::new(0.5, 0.5), Vector::new(1.0, 0.5), Vector::new(0.5, 1.0))
+    }
+
+    pub fn squash_inwards(&self) -> Self {
+        self.transform_painter(Vector::new(0.0, 0.0), Vector::new(0.65, 0.35), Vector::new(0.35, 0.65))
+    }
+
+    // Styles may measure widths and dashes in the unit square; shapes are always in device units
+    pub fn render_lines(&mut self, unit_segments: &Vec<Segment>, style: &Style) {
+        let style = self.device_style(style);
         let segments: Vec<Segment> = unit_segments.iter()
             .map(|segment| self.frame.map_segment(*segment)).collect();
         segments.iter()
-            .for_each(|s| self.shapes.push(Shape::new_line(*s, thickness, colour)));
+            .for_each(|s| self.shapes.push(Shape::new_line(*s, style.clone())));
+    }
+
+    // A single joined path, rather than separate lines
+    pub fn render_path(&mut self, unit_points: &[Vector], closed: bool, style: &Style) {
+        let points = unit_points.iter().map(|p| self.frame.map(*p)).collect();
+        self.shapes.push(Shape::new_poly_line(points, closed, self.device_style(style)));
+    }
+
+    // A path of curves, which stays exact through the frame: curves are only flattened when drawn
+    pub fn render_curves(&mut self, unit_curves: &[Curve], closed: bool, style: &Style) {
+        let curves = unit_curves.iter().map(|curve| curve.map(&self.frame)).collect();
+        self.shapes.push(Shape::new_path(curves, closed, self.device_style(style)));
     }
 
-    pub fn render_circle(&mut self, centre: &Vector, radius: f32, colour: Color) {
+    // The radius is in the unit square, so the circle is stretched and skewed along with the frame
+    pub fn render_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
         let centre = self.frame.map(*centre);
-        self.shapes.push(Shape::new_circle(&centre, radius, colour));
+        self.shapes.push(Shape::new_ellipse(&centre, self.frame.edge1().scale(radius),
+                                            self.frame.edge2().scale(radius), self.device_style(style)));
+    }
+
+    pub fn render_polygon(&mut self, unit_points: &[Vector], style: &Style) {
+        let points = unit_points.iter().map(|p| self.frame.map(*p)).collect();
+        self.shapes.push(Shape::new_polygon(points, self.device_style(style)));
+    }
+
+    // Stays a rectangle while the frame is axis aligned, otherwise becomes the mapped parallelogram
+    pub fn render_rect(&mut self, unit_min: &Vector, unit_max: &Vector, style: &Style) {
+        if self.frame.edge1().y == 0.0 && self.frame.edge2().x == 0.0 {
+            let (corner1, corner2) = (self.frame.map(*unit_min), self.frame.map(*unit_max));
+            let min = Vector::new(corner1.x.min(corner2.x), corner1.y.min(corner2.y));
+            let max = Vector::new(corner1.x.max(corner2.x), corner1.y.max(corner2.y));
+            self.shapes.push(Shape::new_rect(min, max, self.device_style(style)));
+        } else {
+            self.render_polygon(&[*unit_min, Vector::new(unit_max.x, unit_min.y),
+                *unit_max, Vector::new(unit_min.x, unit_max.y)], style);
+        }
+    }
+
+    // Labels stay upright and unstretched whatever the frame; only their position follows it.
+    // The size is in the style's units.
+    pub fn render_text(&mut self, text: &str, unit_position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        let size = style.to_device_length(size, self.frame.scale());
+        self.shapes.push(Shape::new_text(text, self.frame.map(*unit_position), size, anchor,
+                                         self.device_style(style)));
+    }
+
+    // SICP's image painter: the image fills the frame, so it flips and squashes with it
+    pub fn render_image(&mut self, image: &Rc<Image>) {
+        self.shapes.push(Shape::new_image(image, self.frame));
+    }
+
+    fn device_style(&self, style: &Style) -> Style {
+        style.to_device(self.frame.scale())
+    }
+
+    // The area the painter paints into
+    pub fn bounds(&self) -> Bounds {
+        self.frame.bounds()
+    }
+
+    // What has been rendered so far, before any clipping, which may spill outside the frame
+    pub fn content_bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.shapes.iter().filter_map(Shape::bounds))
     }
 
     pub fn paint(&mut self) -> Vec<Shape> {
-        let result = self.shapes.clone();
-        self.shapes = vec![];
-        result
+        let result = std::mem::take(&mut self.shapes);
+        if self.clip {
+            result.iter().flat_map(|shape| clip::clip_shape(shape, &self.frame)).collect()
+        } else {
+            result
+        }
     }
 
     pub fn tessellate_to_right(&self, count: usize) -> CompositePainter {
-        self.tessellate(Vector::new(self.frame.x_max(), 0.0), count)
+        self.tessellate(self.frame.edge1(), count)
     }
 
     pub fn tessellate_to_down(&self, count: usize) -> CompositePainter {
-        self.tessellate(Vector::new(0.0, self.frame.y_max()), count)
+        self.tessellate(self.frame.edge2(), count)
+    }
+
+    pub fn tessellate(&self, translate: Vector, count: usize) -> CompositePainter {
+        Tessellation::new(translate, Vector::zero()).tessellate(self, 1, count)
+    }
+
+    pub fn tessellate_grid(&self, rows: usize, columns: usize) -> CompositePainter {
+        Tessellation::grid(&self.frame).tessellate(self, rows, columns)
     }
 
-    pub fn tessellate(&self, _translate_initial: Vector, count: usize) -> CompositePainter {
-        let mut translate = Vector::zero();
-        let painters = (0..count)
-            .map(|_| {
-                translate = translate + translate;
-                self.transform(translate)
-            })
-            .collect();
-        CompositePainter::new(painters)
+    pub fn tessellate_brick(&self, rows: usize, columns: usize) -> CompositePainter {
+        Tessellation::brick(&self.frame).tessellate(self, rows, columns)
     }
 
-    fn transform(&self, v: Vector) -> Self {
-        Painter::new(self.frame.transform(v))
+    pub fn tessellate_hexagonal(&self, rows: usize, columns: usize) -> CompositePainter {
+        Tessellation::hexagonal(&self.frame).tessellate(self, rows, columns)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+    use crate::picture_language::style::Units;
+
+    #[test]
+    fn render_circle_follows_the_frame() {
+        let frame = Frame::new(Vector::new(10.0, 20.0),
+                               Vector::new(100.0, 20.0),
+                               Vector::new(20.0, 200.0));
+        let mut painter = Painter::new(frame);
+        painter.render_circle(&Vector::new(0.5, 0.5), 0.25, &Style::fill(Colour::RED));
+        assert_eq!(painter.paint(), vec![
+            Shape::new_ellipse(&Vector::new(70.0, 130.0), Vector::new(25.0, 5.0), Vector::new(5.0, 50.0),
+                               Style::fill(Colour::RED)),
+        ]);
+    }
+
+    #[test]
+    fn render_path_follows_the_frame() {
+        let mut painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                                  Vector::new(100.0, 0.0),
+                                                  Vector::new(0.0, 200.0)));
+        let style = Style::stroke(2.0, Colour::RED);
+        painter.render_path(&[Vector::zero(), Vector::new(0.5, 0.5), Vector::new(1.0, 0.0)], true, &style);
+        assert_eq!(painter.paint(), vec![
+            Shape::new_poly_line(vec![Vector::new(10.0, 20.0), Vector::new(60.0, 120.0), Vector::new(110.0, 20.0)],
+                                 true, style),
+        ]);
+    }
+
+    #[test]
+    fn render_curves_follows_the_frame() {
+        let mut painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                                  Vector::new(100.0, 0.0),
+                                                  Vector::new(0.0, 200.0)));
+        let style = Style::stroke(2.0, Colour::RED);
+        painter.render_curves(&[
+            Curve::Quadratic(Vector::zero(), Vector::new(0.5, 1.0), Vector::new(1.0, 0.0)),
+            Curve::circular_arc(Vector::new(0.5, 0.0), 0.5, 0.0, std::f32::consts::PI),
+        ], false, &style);
+        assert_eq!(painter.paint(), vec![
+            Shape::new_path(vec![
+                Curve::Quadratic(Vector::new(10.0, 20.0), Vector::new(60.0, 220.0), Vector::new(110.0, 20.0)),
+                Curve::Arc(Vector::new(60.0, 20.0), Vector::new(50.0, 0.0), Vector::new(0.0, 100.0),
+                           0.0, std::f32::consts::PI),
+            ], false, style),
+        ]);
+    }
+
+    #[test]
+    fn unit_square_widths_scale_with_the_frame() {
+        let mut painter = Painter::new(Frame::new(Vector::zero(),
+                                                  Vector::new(200.0, 0.0),
+                                                  Vector::new(0.0, 50.0)));
+        let unit = Style::stroke(0.01, Colour::BLACK).with_dash(vec![0.1, 0.05]).with_units(Units::UnitSquare);
+        painter.render_lines(&vec![Segment::new(Vector::zero(), Vector::one())], &unit);
+        painter.render_lines(&vec![Segment::new(Vector::zero(), Vector::one())], &Style::stroke(3.0, Colour::BLACK));
+        assert_eq!(painter.paint(), vec![
+            Shape::new_line(Segment::new(Vector::zero(), Vector::new(200.0, 50.0)),
+                            Style::stroke(1.0, Colour::BLACK).with_dash(vec![10.0, 5.0])),
+            Shape::new_line(Segment::new(Vector::zero(), Vector::new(200.0, 50.0)),
+                            Style::stroke(3.0, Colour::BLACK)),
+        ]);
+    }
+
+    #[test]
+    fn render_text() {
+        let mut painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                                  Vector::new(200.0, 0.0),
+                                                  Vector::new(0.0, 50.0)));
+        painter.render_text("origin", &Vector::zero(), 12.0, Anchor::BottomLeft, &Style::fill(Colour::BLACK));
+        let unit = Style::fill(Colour::BLACK).with_units(Units::UnitSquare);
+        painter.render_text("middle", &Vector::new(0.5, 0.5), 0.1, Anchor::Centre, &unit);
+        assert_eq!(painter.paint(), vec![
+            Shape::new_text("origin", Vector::new(10.0, 20.0), 12.0, Anchor::BottomLeft, Style::fill(Colour::BLACK)),
+            Shape::new_text("middle", Vector::new(110.0, 45.0), 10.0, Anchor::Centre, Style::fill(Colour::BLACK)),
+        ]);
+    }
+
+    #[test]
+    fn render_image_fills_the_frame() {
+        let image = Rc::new(Image::new(1, 1, vec![[0, 0, 0, 255]]));
+        let painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                              Vector::new(100.0, 0.0),
+                                              Vector::new(0.0, 200.0)));
+        let mut flipped = painter.flip_vert();
+        flipped.render_image(&image);
+        assert_eq!(flipped.paint(), vec![
+            Shape::new_image(&image, Frame::new(Vector::new(10.0, 220.0),
+                                                Vector::new(100.0, 0.0),
+                                                Vector::new(0.0, -200.0))),
+        ]);
+    }
+
+    #[test]
+    fn clipped_painters_stay_in_their_frame() {
+        let frame = Frame::new(Vector::zero(), Vector::new(100.0, 0.0), Vector::new(0.0, 100.0));
+        let segments = vec![Segment::new(Vector::new(-1.0, 0.5), Vector::new(2.0, 0.5))];
+        let style = Style::stroke(1.0, Colour::BLACK);
+        let mut unclipped = Painter::new(frame);
+        unclipped.render_lines(&segments, &style);
+        assert_eq!(unclipped.paint(), vec![
+            Shape::new_line(Segment::new(Vector::new(-100.0, 50.0), Vector::new(200.0, 50.0)), style.clone()),
+        ]);
+        let mut clipped = Painter::new(frame).clipped().right();
+        assert!(clipped.is_clipped());
+        clipped.render_lines(&segments, &style);
+        assert_eq!(clipped.paint(), vec![
+            Shape::new_line(Segment::new(Vector::new(100.0, 50.0), Vector::new(200.0, 50.0)), style),
+        ]);
+    }
+
+    #[test]
+    fn bounds() {
+        let mut painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                                  Vector::new(100.0, 0.0),
+                                                  Vector::new(0.0, 200.0)));
+        assert_eq!(painter.bounds(), Bounds::new(Vector::new(10.0, 20.0), Vector::new(110.0, 220.0)));
+        assert_eq!(painter.content_bounds(), None);
+        painter.render_circle(&Vector::new(1.0, 1.0), 0.1, &Style::fill(Colour::RED));
+        assert_eq!(painter.content_bounds(), Some(Bounds::new(Vector::new(100.0, 200.0), Vector::new(120.0, 240.0))));
+    }
+
+    #[test]
+    fn render_polygon_follows_the_frame() {
+        let frame = Frame::new(Vector::new(10.0, 20.0),
+                               Vector::new(100.0, 20.0),
+                               Vector::new(20.0, 200.0));
+        let mut painter = Painter::new(frame);
+        let style = Style::fill_and_stroke(Colour::RED, 1.0, Colour::BLACK);
+        painter.render_polygon(&[Vector::zero(), Vector::new(1.0, 0.0), Vector::new(0.5, 1.0)], &style);
+        assert_eq!(painter.paint(), vec![
+            Shape::new_polygon(vec![Vector::new(10.0, 20.0), Vector::new(110.0, 40.0), Vector::new(80.0, 230.0)],
+                               style),
+        ]);
+    }
+
+    #[test]
+    fn render_rect() {
+        let mut painter = Painter::new(Frame::new(Vector::new(10.0, 20.0),
+                                                  Vector::new(100.0, 0.0),
+                                                  Vector::new(0.0, 200.0)));
+        painter.render_rect(&Vector::new(0.25, 0.5), &Vector::new(0.75, 1.0), &Style::fill(Colour::BLUE));
+        assert_eq!(painter.paint(), vec![
+            Shape::new_rect(Vector::new(35.0, 120.0), Vector::new(85.0, 220.0), Style::fill(Colour::BLUE)),
+        ]);
+        let mut flipped = painter.flip_horiz();
+        flipped.render_rect(&Vector::zero(), &Vector::new(0.5, 0.5), &Style::fill(Colour::BLUE));
+        assert_eq!(flipped.paint(), vec![
+            Shape::new_rect(Vector::new(60.0, 20.0), Vector::new(110.0, 120.0), Style::fill(Colour::BLUE)),
+        ]);
+    }
+
+    #[test]
+    fn render_rect_in_a_skewed_frame_is_a_polygon() {
+        let mut painter = Painter::new(Frame::new(Vector::zero(),
+                                                  Vector::new(10.0, 10.0),
+                                                  Vector::new(0.0, 10.0)));
+        painter.render_rect(&Vector::zero(), &Vector::one(), &Style::fill(Colour::BLUE));
+        assert_eq!(painter.paint(), vec![
+            Shape::new_polygon(vec![Vector::zero(), Vector::new(10.0, 10.0), Vector::new(10.0, 20.0),
+                                    Vector::new(0.0, 10.0)], Style::fill(Colour::BLUE)),
+        ]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::picture_language::combinators::{below, beside};
+use crate::picture_language::composite_painter::CompositePainter;
+
+// The recursive patterns of SICP 2.2.4, each n levels deep
+
+pub fn right_split(painter: &CompositePainter, n: usize) -> CompositePainter {
+    if n == 0 {
+        painter.clone()
+    } else {
+        let smaller = right_split(painter, n - 1);
+        beside(painter, &below(&smaller, &smaller))
+    }
+}
+
+pub fn up_split(painter: &CompositePainter, n: usize) -> CompositePainter {
+    if n == 0 {
+        painter.clone()
+    } else {
+        let smaller = up_split(painter, n - 1);
+        below(painter, &beside(&smaller, &smaller))
+    }
+}
+
+pub fn corner_split(painter: &CompositePainter, n: usize) -> CompositePainter {
+    if n == 0 {
+        painter.clone()
+    } else {
+        let up = up_split(painter, n - 1);
+        let right = right_split(painter, n - 1);
+        let top_left = beside(&up, &up);
+        let bottom_right = below(&right, &right);
+        let corner = corner_split(painter, n - 1);
+        beside(&below(painter, &top_left), &below(&bottom_right, &corner))
+    }
+}
+
+pub fn square_of_four<TL, TR, BL, BR>(tl: TL, tr: TR, bl: BL, br: BR) -> impl Fn(&CompositePainter) -> CompositePainter
+    where TL: Fn(&CompositePainter) -> CompositePainter,
+          TR: Fn(&CompositePainter) -> CompositePainter,
+          BL: Fn(&CompositePainter) -> CompositePainter,
+          BR: Fn(&CompositePainter) -> CompositePainter {
+    move |painter| {
+        let top = beside(&tl(painter), &tr(painter));
+        let bottom = beside(&bl(painter), &br(painter));
+        below(&bottom, &top)
+    }
+}
+
+pub fn square_limit(painter: &CompositePainter, n: usize) -> CompositePainter {
+    let combine4 = square_of_four(CompositePainter::flip_horiz,
+                                  CompositePainter::clone,
+                                  CompositePainter::rotate180,
+                                  CompositePainter::flip_vert);
+    combine4(&corner_split(painter, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::bounds::Bounds;
+    use crate::picture_language::frame::Frame;
+    use crate::picture_language::painter::Painter;
+    use crate::picture_language::vector::Vector;
+
+    fn painter() -> CompositePainter {
+        Painter::new(Frame::new(Vector::zero(),
+                                Vector::new(160.0, 0.0),
+                                Vector::new(0.0, 160.0))).into()
+    }
+
+    fn frames(composite: &CompositePainter) -> Vec<Frame> {
+        composite.painters().iter().map(|p| p.frame).collect()
+    }
+
+    #[test]
+    fn zero_depth_is_the_painter() {
+        assert_eq!(super::right_split(&painter(), 0), painter());
+        assert_eq!(super::up_split(&painter(), 0), painter());
+        assert_eq!(super::corner_split(&painter(), 0), painter());
+    }
+
+    #[test]
+    fn right_split() {
+        let split = super::right_split(&painter(), 2);
+        assert_eq!(split.painters().len(), 7);
+        assert_eq!(frames(&split)[0],
+                   Frame::new(Vector::zero(), Vector::new(80.0, 0.0), Vector::new(0.0, 160.0)));
+        assert_eq!(frames(&split)[1],
+                   Frame::new(Vector::new(80.0, 0.0), Vector::new(40.0, 0.0), Vector::new(0.0, 80.0)));
+        assert_eq!(frames(&split)[2],
+                   Frame::new(Vector::new(120.0, 0.0), Vector::new(40.0, 0.0), Vector::new(0.0, 40.0)));
+    }
+
+    #[test]
+    fn up_split() {
+        let split = super::up_split(&painter(), 2);
+        assert_eq!(split.painters().len(), 7);
+        assert_eq!(frames(&split)[0],
+                   Frame::new(Vector::zero(), Vector::new(160.0, 0.0), Vector::new(0.0, 80.0)));
+        assert_eq!(frames(&split)[1],
+                   Frame::new(Vector::new(0.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 40.0)));
+    }
+
+    #[test]
+    fn corner_split() {
+        let split = super::corner_split(&painter(), 1);
+        assert_eq!(frames(&split), vec![
+            Frame::new(Vector::zero(), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(0.0, 80.0), Vector::new(40.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(40.0, 80.0), Vector::new(40.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(80.0, 0.0), Vector::new(80.0, 0.0), Vector::new(0.0, 40.0)),
+            Frame::new(Vector::new(80.0, 40.0), Vector::new(80.0, 0.0), Vector::new(0.0, 40.0)),
+            Frame::new(Vector::new(80.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+        ]);
+    }
+
+    #[test]
+    fn square_of_four() {
+        let identity = super::square_of_four(CompositePainter::clone, CompositePainter::clone,
+                                             CompositePainter::clone, CompositePainter::clone);
+        assert_eq!(frames(&identity(&painter())), vec![
+            Frame::new(Vector::zero(), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(80.0, 0.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(0.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+            Frame::new(Vector::new(80.0, 80.0), Vector::new(80.0, 0.0), Vector::new(0.0, 80.0)),
+        ]);
+    }
+
+    #[test]
+    fn square_limit() {
+        let limit = super::square_limit(&painter(), 2);
+        let corner_count = super::corner_split(&painter(), 2).painters().len();
+        assert_eq!(limit.painters().len(), 4 * corner_count);
+        assert_eq!(limit.frame(), painter().frame());
+        // The top right quarter is the corner split unchanged
+        assert_eq!(frames(&limit)[3 * corner_count],
+                   Frame::new(Vector::new(80.0, 80.0), Vector::new(40.0, 0.0), Vector::new(0.0, 40.0)));
+
+        // Each corner split fills its own quarter, and the four fill the frame
+        let quarters: Vec<Bounds> = limit.painters().chunks(corner_count)
+            .map(|painters| Bounds::union_all(painters.iter().map(Painter::bounds)).unwrap())
+            .collect();
+        assert_eq!(quarters, vec![
+            Bounds::new(Vector::zero(), Vector::new(80.0, 80.0)),
+            Bounds::new(Vector::new(80.0, 0.0), Vector::new(160.0, 80.0)),
+            Bounds::new(Vector::new(0.0, 80.0), Vector::new(80.0, 160.0)),
+            Bounds::new(Vector::new(80.0, 80.0), Vector::new(160.0, 160.0)),
+        ]);
+        for (i, q1) in quarters.iter().enumerate() {
+            for q2 in &quarters[i + 1..] {
+                assert!(q1.max.x <= q2.min.x || q1.max.y <= q2.min.y);
+            }
+        }
+    }
+}
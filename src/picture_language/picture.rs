@@ -1,12 +1,59 @@
+use crate::picture_language::curve::Curve;
+use std::rc::Rc;
 use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
 use crate::picture_language::segment::Segment;
 use crate::picture_language::vector::Vector;
-use crate::picture_language::window_handler;
-use speedy2d::color::Color;
+#[cfg(feature = "window")]
+use crate::picture_language::{window, window_handler::{self, ViewerOptions}};
+use crate::picture_language::colour::Colour;
+use crate::picture_language::coordinates::CoordinateSystem;
 use crate::picture_language::painter::{Painter};
 use crate::picture_language::composite_painter::{CompositePainter};
+use crate::picture_language::shape::{Anchor, Shape};
+use crate::picture_language::style::{LineCap, Style, Units};
+use crate::picture_language::picture_file::{self, Persistent};
+use crate::picture_language::{dsl, raster, svg};
 
-pub fn run_picture() {
+// What to draw, as given on the command line
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PictureOptions {
+    pub program: Option<String>, // a picture written in the DSL, such as (square-limit wave 4)
+    pub image: Option<String>, // a PNG or PPM drawn into every frame of the demo
+}
+
+#[cfg(feature = "window")]
+pub fn run_picture(options: &PictureOptions) -> std::io::Result<()> {
+    let (shapes, coordinates) = picture(options)?;
+    window_handler::run_viewer(shapes, &ViewerOptions::new().with_fit(true).with_coordinates(coordinates));
+    Ok(())
+}
+
+// Draws a tile in the editor, saving it to this file
+#[cfg(feature = "window")]
+pub fn edit_tile(path: &str) {
+    window::run_window(path);
+}
+
+pub fn export_picture(path: &str, options: &PictureOptions) -> std::io::Result<()> {
+    let (shapes, coordinates) = picture(options)?;
+    if path.ends_with(".png") {
+        raster::write_png(path, &shapes, 800, 800, coordinates)
+    } else {
+        svg::write_svg(path, &shapes, 800, 800, coordinates)
+    }
+}
+
+// A DSL program is drawn with y up as in SICP, and one that fails is an error rather than the demo
+fn picture(options: &PictureOptions) -> std::io::Result<(Vec<Shape>, CoordinateSystem)> {
+    if let Some(path) = &options.program {
+        let frame = Frame::new(Vector::zero(), Vector::new(800.0, 0.0), Vector::new(0.0, 800.0));
+        return Ok((dsl::paint_file(path, frame)?.paint(), CoordinateSystem::YUp));
+    }
+    Ok((demo(options.image.as_deref()), CoordinateSystem::YDown))
+}
+
+fn demo(image: Option<&str>) -> Vec<Shape> {
     let frame = Frame::new(Vector::zero(),
                            Vector::new(100.0, 20.0),
                            Vector::new(20.0, 200.0));
@@ -14,34 +61,81 @@ pub fn run_picture() {
     let painter2 = painter1.right();
     let painter3 = painter1.below();
     let mut painter = CompositePainter::new(vec![painter1, painter2, painter3]);
-    painter.render_lines(&outer_bounds(), 1.0, Color::RED);
-    painter.render_lines(&cross(), 1.0, Color::GREEN);
-    painter.render_lines(&diamond(), 1.0, Color::BLACK);
-    painter.render_circle(&Vector::new(0.5, 0.5), 0.3, Color::RED);
-    window_handler::run_picture_window(painter.paint());
+    painter.render_rect(&Vector::zero(), &Vector::one(), &Style::fill(Colour::from_rgb(1.0, 0.95, 0.8)));
+    // SICP's rogers: any PNG or PPM, such as a portrait, drawn into every frame
+    if let Some(path) = image {
+        match Image::load(path) {
+            Ok(image) => painter.render_image(&Rc::new(image)),
+            Err(error) => eprintln!("{}: {}", path, error),
+        }
+    }
+    painter.render_path(&outer_bounds(), true, &Style::stroke(1.0, Colour::RED));
+    painter.render_lines(&cross(), &Style::stroke(0.05, Colour::GREEN).with_dash(vec![0.1, 0.05])
+        .with_units(Units::UnitSquare).with_opacity(0.5));
+    painter.render_path(&diamond(), true, &Style::stroke(1.0, Colour::BLACK));
+    painter.render_circle(&Vector::new(0.5, 0.5), 0.3, &Style::fill_and_stroke(Colour::YELLOW, 2.0, Colour::RED));
+    let pen = Style::stroke(0.02, Colour::BLACK).with_units(Units::UnitSquare).with_cap(LineCap::Round);
+    smile().iter().for_each(|curve| painter.render_curves(&[*curve], false, &pen));
+    painter.render_text("frame", &Vector::new(0.5, 0.0), 12.0, Anchor::Top, &Style::fill(Colour::BLACK));
+    painter.paint()
 }
 
-fn outer_bounds() -> Vec<Segment> {
-    let top_right = Vector::new(1.0, 0.0);
-    let bottom_left = Vector::new(0.0, 1.0);
-    Segment::open_path(&vec![Vector::zero(), top_right, Vector::one(), bottom_left, Vector::zero()])
+// Tiles designed in picture files, kept next to this one
+pub fn outer_bounds() -> Vec<Vector> {
+    tile(include_str!("tiles/outer_bounds.sexp"))
 }
 
-fn diamond() -> Vec<Segment> {
-    Segment::open_path(&vec![
-        Vector::new(0.0, 0.5),
-        Vector::new(0.5, 0.0),
-        Vector::new(1.0, 0.5),
-        Vector::new(0.5, 1.0),
-        Vector::new(0.0, 0.5),
-    ])
+pub fn diamond() -> Vec<Vector> {
+    tile(include_str!("tiles/diamond.sexp"))
 }
 
-fn cross() -> Vec<Segment> {
-    let top_right = Vector::new(1.0, 0.0);
-    let bottom_left = Vector::new(0.0, 1.0);
+pub fn cross() -> Vec<Segment> {
+    tile(include_str!("tiles/cross.sexp"))
+}
+
+pub fn wave() -> Vec<Segment> {
+    tile(include_str!("tiles/wave.sexp"))
+}
+
+fn tile<T: Persistent>(text: &str) -> T {
+    picture_file::load(text).expect("tiles are valid picture files")
+}
+
+// Eyes and a mouth inside the circle
+fn smile() -> Vec<Curve> {
+    let eye = |x: f32| Curve::circular_arc(Vector::new(x, 0.4), 0.04, 0.0, 2.0 * std::f32::consts::PI);
     vec![
-        Segment::new(top_right, bottom_left),
-        Segment::new(Vector::zero(), Vector::new(1.0, 1.0)),
+        eye(0.4),
+        eye(0.6),
+        Curve::Cubic(Vector::new(0.35, 0.6), Vector::new(0.4, 0.72), Vector::new(0.6, 0.72), Vector::new(0.65, 0.6)),
     ]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles() {
+        assert_eq!(outer_bounds(), vec![Vector::zero(), Vector::new(1.0, 0.0), Vector::one(), Vector::new(0.0, 1.0)]);
+        assert_eq!(diamond()[1], Vector::new(0.5, 0.0));
+        assert_eq!(cross(), vec![
+            Segment::new(Vector::new(1.0, 0.0), Vector::new(0.0, 1.0)),
+            Segment::new(Vector::zero(), Vector::one()),
+        ]);
+        assert_eq!(wave().len(), 17);
+    }
+
+    #[test]
+    fn export_a_failing_program() {
+        let dir = std::env::temp_dir();
+        let program = dir.join(format!("sicp-unbound-{}.scm", std::process::id()));
+        let output = dir.join(format!("sicp-unbound-{}.svg", std::process::id()));
+        std::fs::write(&program, "(beside wave no-such-painter)").unwrap();
+        let options = PictureOptions { program: Some(program.to_str().unwrap().to_string()), image: None };
+        let exported = export_picture(output.to_str().unwrap(), &options);
+        std::fs::remove_file(&program).unwrap();
+        assert_eq!(exported.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert!(!output.exists());
+    }
+}
@@ -0,0 +1,506 @@
+use std::fs;
+use std::io;
+use std::rc::Rc;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::curve::Curve;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
+use crate::picture_language::painter::Painter;
+use crate::picture_language::segment::Segment;
+use crate::picture_language::sexpr::{self, Expr};
+use crate::picture_language::shape::{Anchor, Shape};
+use crate::picture_language::style::{LineCap, LineJoin, Style, Units};
+use crate::picture_language::vector::Vector;
+
+// Pictures saved as S-expressions, so tiles can be kept in the repository and edited by hand:
+//
+//   (sicp-picture 1
+//     ((0 0) (1 0) (1 1) (0 1)))
+//
+// The number is the format's version. Files from newer versions are refused rather than
+// misread; any later version must still read version 1 files.
+pub const VERSION: u32 = 1;
+
+const HEADER: &str = "sicp-picture";
+
+// Anything that can be saved in a picture file
+pub trait Persistent: Sized {
+    fn to_expr(&self) -> Expr;
+    fn from_expr(expr: &Expr) -> io::Result<Self>;
+}
+
+pub fn save<T: Persistent>(value: &T) -> String {
+    let file = Expr::list(vec![Expr::symbol(HEADER), Expr::Number(VERSION as f32), value.to_expr()]);
+    file.pretty() + "\n"
+}
+
+pub fn load<T: Persistent>(text: &str) -> io::Result<T> {
+    let file = sexpr::parse(text)?;
+    match file.tagged(HEADER) {
+        Some([Expr::Number(version), value]) if *version >= 1.0 && *version <= VERSION as f32 => T::from_expr(value),
+        Some([Expr::Number(version), _]) => Err(invalid(&format!("unsupported picture file version {}", version))),
+        _ => Err(invalid(&format!("not a picture file: expected ({} {} ...)", HEADER, VERSION))),
+    }
+}
+
+pub fn write_file<T: Persistent>(path: &str, value: &T) -> io::Result<()> {
+    fs::write(path, save(value))
+}
+
+pub fn read_file<T: Persistent>(path: &str) -> io::Result<T> {
+    load(&fs::read_to_string(path)?).map_err(|error| invalid(&format!("{}: {}", path, error)))
+}
+
+impl Persistent for f32 {
+    fn to_expr(&self) -> Expr {
+        Expr::Number(*self)
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        expr.as_number().ok_or_else(|| expected("a number", expr))
+    }
+}
+
+impl<T: Persistent> Persistent for Vec<T> {
+    fn to_expr(&self) -> Expr {
+        Expr::list(self.iter().map(T::to_expr).collect())
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        expr.as_list().ok_or_else(|| expected("a list", expr))?.iter().map(T::from_expr).collect()
+    }
+}
+
+// (x y)
+impl Persistent for Vector {
+    fn to_expr(&self) -> Expr {
+        Expr::list(vec![Expr::Number(self.x), Expr::Number(self.y)])
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        match expr.as_list() {
+            Some([Expr::Number(x), Expr::Number(y)]) => Ok(Vector::new(*x, *y)),
+            _ => Err(expected("a vector (x y)", expr)),
+        }
+    }
+}
+
+// (segment start end)
+impl Persistent for Segment {
+    fn to_expr(&self) -> Expr {
+        tagged("segment", vec![self.start.to_expr(), self.end.to_expr()])
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        match expr.tagged("segment") {
+            Some([start, end]) => Ok(Segment::new(Vector::from_expr(start)?, Vector::from_expr(end)?)),
+            _ => Err(expected("(segment start end)", expr)),
+        }
+    }
+}
+
+// (frame origin edge1 edge2)
+impl Persistent for Frame {
+    fn to_expr(&self) -> Expr {
+        tagged("frame", vec![self.origin().to_expr(), self.edge1().to_expr(), self.edge2().to_expr()])
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        match expr.tagged("frame") {
+            Some([origin, edge1, edge2]) =>
+                Ok(Frame::new(Vector::from_expr(origin)?, Vector::from_expr(edge1)?, Vector::from_expr(edge2)?)),
+            _ => Err(expected("(frame origin edge1 edge2)", expr)),
+        }
+    }
+}
+
+// (rgba r g b a), each from 0 to 1
+impl Persistent for Colour {
+    fn to_expr(&self) -> Expr {
+        tagged("rgba", [self.r, self.g, self.b, self.a].iter().map(f32::to_expr).collect())
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        match expr.tagged("rgba") {
+            Some([Expr::Number(r), Expr::Number(g), Expr::Number(b), Expr::Number(a)]) =>
+                Ok(Colour::from_rgba(*r, *g, *b, *a)),
+            _ => Err(expected("(rgba r g b a)", expr)),
+        }
+    }
+}
+
+// (style (stroke colour) (fill colour) (width w) (units unit-square) (dash on off ...)
+//        (join round) (cap round) (opacity a))
+// Every property may be left out: there is then no stroke or fill, a width of 1, no dashes,
+// and the defaults of Style.
+impl Persistent for Style {
+    fn to_expr(&self) -> Expr {
+        let default = default_style();
+        let property = |name: &str, values: Vec<Expr>| tagged(name, values);
+        let mut properties = vec![];
+        properties.extend(self.stroke.map(|colour| property("stroke", vec![colour.to_expr()])));
+        properties.extend(self.fill.map(|colour| property("fill", vec![colour.to_expr()])));
+        properties.push(property("width", vec![self.width.to_expr()]));
+        if self.units != default.units {
+            properties.push(property("units", vec![Expr::symbol(units_name(self.units))]));
+        }
+        if !self.dash.is_empty() {
+            properties.push(property("dash", self.dash.iter().map(f32::to_expr).collect()));
+        }
+        if self.join != default.join {
+            properties.push(property("join", vec![Expr::symbol(join_name(self.join))]));
+        }
+        if self.cap != default.cap {
+            properties.push(property("cap", vec![Expr::symbol(cap_name(self.cap))]));
+        }
+        if self.opacity != default.opacity {
+            properties.push(property("opacity", vec![self.opacity.to_expr()]));
+        }
+        tagged("style", properties)
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        let properties = expr.tagged("style").ok_or_else(|| expected("(style ...)", expr))?;
+        let mut style = default_style();
+        for property in properties {
+            let (name, values) = match property.as_list() {
+                Some([Expr::Symbol(name), values @ ..]) => (name.as_str(), values),
+                _ => return Err(expected("a style property such as (width 1)", property)),
+            };
+            let symbol = match values {
+                [Expr::Symbol(value)] => value.as_str(),
+                _ => "",
+            };
+            let number = || match values {
+                [value] => f32::from_expr(value),
+                _ => Err(expected("a number", property)),
+            };
+            match name {
+                "stroke" | "fill" => {
+                    let colour = match values {
+                        [colour] => Colour::from_expr(colour)?,
+                        _ => return Err(expected("a colour", property)),
+                    };
+                    if name == "stroke" { style.stroke = Some(colour) } else { style.fill = Some(colour) }
+                }
+                "width" => style.width = number()?,
+                "opacity" => style.opacity = number()?,
+                "dash" => style.dash = values.iter().map(f32::from_expr).collect::<io::Result<_>>()?,
+                "units" => style.units = [Units::Device, Units::UnitSquare].into_iter()
+                    .find(|units| units_name(*units) == symbol)
+                    .ok_or_else(|| expected("device or unit-square", property))?,
+                "join" => style.join = [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel].into_iter()
+                    .find(|join| join_name(*join) == symbol)
+                    .ok_or_else(|| expected("miter, round or bevel", property))?,
+                "cap" => style.cap = [LineCap::Butt, LineCap::Round, LineCap::Square].into_iter()
+                    .find(|cap| cap_name(*cap) == symbol)
+                    .ok_or_else(|| expected("butt, round or square", property))?,
+                _ => return Err(invalid(&format!("unknown style property {}", name))),
+            }
+        }
+        Ok(style)
+    }
+}
+
+fn default_style() -> Style {
+    Style { stroke: None, ..Style::stroke(1.0, Colour::BLACK) }
+}
+
+fn units_name(units: Units) -> &'static str {
+    match units {
+        Units::Device => "device",
+        Units::UnitSquare => "unit-square",
+    }
+}
+
+fn join_name(join: LineJoin) -> &'static str {
+    match join {
+        LineJoin::Miter => "miter",
+        LineJoin::Round => "round",
+        LineJoin::Bevel => "bevel",
+    }
+}
+
+fn cap_name(cap: LineCap) -> &'static str {
+    match cap {
+        LineCap::Butt => "butt",
+        LineCap::Round => "round",
+        LineCap::Square => "square",
+    }
+}
+
+const ANCHORS: [(Anchor, &str); 9] = [
+    (Anchor::TopLeft, "top-left"),
+    (Anchor::Top, "top"),
+    (Anchor::TopRight, "top-right"),
+    (Anchor::Left, "left"),
+    (Anchor::Centre, "centre"),
+    (Anchor::Right, "right"),
+    (Anchor::BottomLeft, "bottom-left"),
+    (Anchor::Bottom, "bottom"),
+    (Anchor::BottomRight, "bottom-right"),
+];
+
+impl Persistent for Anchor {
+    fn to_expr(&self) -> Expr {
+        Expr::symbol(ANCHORS.iter().find(|(anchor, _)| anchor == self).unwrap().1)
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        ANCHORS.iter().find(|(_, name)| expr.as_symbol() == Some(name)).map(|(anchor, _)| *anchor)
+            .ok_or_else(|| expected("an anchor such as centre or top-left", expr))
+    }
+}
+
+// (line start end), (quadratic p0 p1 p2), (cubic p0 p1 p2 p3) or
+// (arc centre axis1 axis2 start-angle end-angle)
+impl Persistent for Curve {
+    fn to_expr(&self) -> Expr {
+        match self {
+            Curve::Line(start, end) => tagged("line", vec![start.to_expr(), end.to_expr()]),
+            Curve::Quadratic(p0, p1, p2) => tagged("quadratic", vec![p0.to_expr(), p1.to_expr(), p2.to_expr()]),
+            Curve::Cubic(p0, p1, p2, p3) =>
+                tagged("cubic", vec![p0.to_expr(), p1.to_expr(), p2.to_expr(), p3.to_expr()]),
+            Curve::Arc(centre, axis1, axis2, start, end) =>
+                tagged("arc", vec![centre.to_expr(), axis1.to_expr(), axis2.to_expr(), start.to_expr(), end.to_expr()]),
+        }
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        let v = Vector::from_expr;
+        match expr.as_list() {
+            Some([Expr::Symbol(tag), rest @ ..]) => match (tag.as_str(), rest) {
+                ("line", [start, end]) => Ok(Curve::Line(v(start)?, v(end)?)),
+                ("quadratic", [p0, p1, p2]) => Ok(Curve::Quadratic(v(p0)?, v(p1)?, v(p2)?)),
+                ("cubic", [p0, p1, p2, p3]) => Ok(Curve::Cubic(v(p0)?, v(p1)?, v(p2)?, v(p3)?)),
+                ("arc", [centre, axis1, axis2, start, end]) =>
+                    Ok(Curve::Arc(v(centre)?, v(axis1)?, v(axis2)?, f32::from_expr(start)?, f32::from_expr(end)?)),
+                _ => Err(expected("a curve", expr)),
+            },
+            _ => Err(expected("a curve", expr)),
+        }
+    }
+}
+
+// (line start end style), (polyline open|closed (points ...) style),
+// (path open|closed (curves ...) style), (circle centre radius style),
+// (ellipse centre axis1 axis2 style), (polygon (points ...) style), (rect min max style),
+// (text "text" position size anchor style) or (image frame "base64 PNG")
+impl Persistent for Shape {
+    fn to_expr(&self) -> Expr {
+        match self {
+            Shape::Line(segment, style) =>
+                tagged("line", vec![segment.start.to_expr(), segment.end.to_expr(), style.to_expr()]),
+            Shape::PolyLine(points, closed, style) =>
+                tagged("polyline", vec![closed_expr(*closed), points.to_expr(), style.to_expr()]),
+            Shape::Path(curves, closed, style) =>
+                tagged("path", vec![closed_expr(*closed), curves.to_expr(), style.to_expr()]),
+            Shape::Circle(centre, radius, style) =>
+                tagged("circle", vec![centre.to_expr(), radius.to_expr(), style.to_expr()]),
+            Shape::Ellipse(centre, axis1, axis2, style) =>
+                tagged("ellipse", vec![centre.to_expr(), axis1.to_expr(), axis2.to_expr(), style.to_expr()]),
+            Shape::Polygon(points, style) => tagged("polygon", vec![points.to_expr(), style.to_expr()]),
+            Shape::Rect(min, max, style) => tagged("rect", vec![min.to_expr(), max.to_expr(), style.to_expr()]),
+            Shape::Text(text, position, size, anchor, style) => tagged("text", vec![
+                Expr::Str(text.clone()), position.to_expr(), size.to_expr(), anchor.to_expr(), style.to_expr(),
+            ]),
+            // Opaque images are saved as before
+            Shape::Image(image, frame, opacity) => {
+                let mut items = vec![frame.to_expr(), Expr::Str(image.to_png_base64())];
+                if *opacity != 1.0 {
+                    items.push(opacity.to_expr());
+                }
+                tagged("image", items)
+            }
+        }
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        let (v, s) = (Vector::from_expr, Style::from_expr);
+        let Some([Expr::Symbol(tag), rest @ ..]) = expr.as_list() else { return Err(expected("a shape", expr)) };
+        match (tag.as_str(), rest) {
+            ("line", [start, end, style]) => Ok(Shape::new_line(Segment::new(v(start)?, v(end)?), s(style)?)),
+            ("polyline", [closed, points, style]) =>
+                Ok(Shape::new_poly_line(Vec::from_expr(points)?, closed_from_expr(closed)?, s(style)?)),
+            ("path", [closed, curves, style]) =>
+                Ok(Shape::new_path(Vec::from_expr(curves)?, closed_from_expr(closed)?, s(style)?)),
+            ("circle", [centre, radius, style]) => Ok(Shape::new_circle(&v(centre)?, f32::from_expr(radius)?, s(style)?)),
+            ("ellipse", [centre, axis1, axis2, style]) =>
+                Ok(Shape::new_ellipse(&v(centre)?, v(axis1)?, v(axis2)?, s(style)?)),
+            ("polygon", [points, style]) => Ok(Shape::new_polygon(Vec::from_expr(points)?, s(style)?)),
+            ("rect", [min, max, style]) => Ok(Shape::new_rect(v(min)?, v(max)?, s(style)?)),
+            ("text", [Expr::Str(text), position, size, anchor, style]) =>
+                Ok(Shape::new_text(text, v(position)?, f32::from_expr(size)?, Anchor::from_expr(anchor)?, s(style)?)),
+            ("image", [frame, Expr::Str(png)]) =>
+                Ok(Shape::new_image(&Rc::new(Image::from_png_base64(png)?), Frame::from_expr(frame)?)),
+            ("image", [frame, Expr::Str(png), opacity]) => Ok(Shape::Image(Rc::new(Image::from_png_base64(png)?),
+                                                                         Frame::from_expr(frame)?,
+                                                                         f32::from_expr(opacity)?)),
+            _ => Err(expected("a shape", expr)),
+        }
+    }
+}
+
+fn closed_expr(closed: bool) -> Expr {
+    Expr::symbol(if closed { "closed" } else { "open" })
+}
+
+fn closed_from_expr(expr: &Expr) -> io::Result<bool> {
+    match expr.as_symbol() {
+        Some("closed") => Ok(true),
+        Some("open") => Ok(false),
+        _ => Err(expected("open or closed", expr)),
+    }
+}
+
+// (painter frame (shapes ...)), with clipped after the frame for a clipped painter
+impl Persistent for Painter {
+    fn to_expr(&self) -> Expr {
+        let clipped = self.is_clipped().then(|| Expr::symbol("clipped"));
+        let items = [Some(self.frame.to_expr()), clipped, Some(self.shapes().to_vec().to_expr())];
+        tagged("painter", items.into_iter().flatten().collect())
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        let (frame, clipped, shapes) = match expr.tagged("painter") {
+            Some([frame, shapes]) => (frame, false, shapes),
+            Some([frame, clipped, shapes]) if clipped.as_symbol() == Some("clipped") => (frame, true, shapes),
+            _ => return Err(expected("(painter frame (shapes ...))", expr)),
+        };
+        let painter = Painter::new(Frame::from_expr(frame)?).with_shapes(Vec::from_expr(shapes)?);
+        Ok(if clipped { painter.clipped() } else { painter })
+    }
+}
+
+// (composite frame (painters ...))
+impl Persistent for CompositePainter {
+    fn to_expr(&self) -> Expr {
+        tagged("composite", vec![self.frame().to_expr(), self.painters().to_vec().to_expr()])
+    }
+
+    fn from_expr(expr: &Expr) -> io::Result<Self> {
+        match expr.tagged("composite") {
+            Some([frame, painters]) =>
+                Ok(CompositePainter::new(Vec::from_expr(painters)?).with_frame(Frame::from_expr(frame)?)),
+            _ => Err(expected("(composite frame (painters ...))", expr)),
+        }
+    }
+}
+
+fn tagged(tag: &str, items: Vec<Expr>) -> Expr {
+    Expr::list([vec![Expr::symbol(tag)], items].concat())
+}
+
+fn expected(what: &str, found: &Expr) -> io::Error {
+    let found = found.to_string();
+    let found = if found.chars().count() > 40 { found.chars().take(40).collect::<String>() + "..." } else { found };
+    invalid(&format!("expected {}, found {}", what, found))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn round_trip<T: Persistent + PartialEq + std::fmt::Debug>(value: T) {
+        assert_eq!(load::<T>(&save(&value)).unwrap(), value);
+    }
+
+    fn frame() -> Frame {
+        Frame::new(Vector::new(10.0, 20.0), Vector::new(100.0, 0.1), Vector::new(-20.0, 200.0))
+    }
+
+    #[test]
+    fn format() {
+        let points = vec![Vector::zero(), Vector::new(1.0, 0.0), Vector::new(0.5, -0.25)];
+        assert_eq!(save(&points), "(sicp-picture 1 ((0 0) (1 0) (0.5 -0.25)))\n");
+        assert_eq!(save(&Shape::new_circle(&Vector::one(), 0.3, Style::fill(Colour::RED).with_opacity(0.5))),
+                   "(sicp-picture 1 (circle (1 1) 0.3 (style (fill (rgba 1 0 0 1)) (width 0) (opacity 0.5))))\n");
+    }
+
+    #[test]
+    fn geometry_round_trips() {
+        round_trip(Vector::new(0.1, -1e-7));
+        round_trip(Segment::new(Vector::zero(), Vector::new(1.0 / 3.0, 2.0)));
+        round_trip(frame());
+        round_trip(vec![Curve::Line(Vector::zero(), Vector::one()),
+                        Curve::Quadratic(Vector::zero(), Vector::one(), Vector::new(2.0, 0.0)),
+                        Curve::Cubic(Vector::zero(), Vector::one(), Vector::new(2.0, 0.0), Vector::new(3.0, 1.0)),
+                        Curve::circular_arc(Vector::one(), 0.5, 0.0, PI)]);
+    }
+
+    #[test]
+    fn shapes_round_trip() {
+        let style = Style::fill_and_stroke(Colour::YELLOW, 2.0, Colour::RED)
+            .with_dash(vec![0.1, 0.05]).with_units(Units::UnitSquare)
+            .with_join(LineJoin::Bevel).with_cap(LineCap::Square).with_opacity(0.25);
+        let points = vec![Vector::zero(), Vector::new(1.0, 0.0), Vector::one()];
+        round_trip(vec![
+            Shape::new_line(Segment::new(Vector::zero(), Vector::one()), Style::stroke(1.0, Colour::BLACK)),
+            Shape::new_poly_line(points.clone(), true, style.clone()),
+            Shape::new_path(vec![Curve::circular_arc(Vector::one(), 0.5, 0.0, PI)], false, style.clone()),
+            Shape::new_circle(&Vector::one(), 3.0, style.clone()),
+            Shape::new_ellipse(&Vector::one(), Vector::new(2.0, 1.0), Vector::new(0.0, 3.0), style.clone()),
+            Shape::new_polygon(points, style.clone()),
+            Shape::new_rect(Vector::zero(), Vector::one(), style.clone()),
+            Shape::new_text("say \"cheese\"", Vector::one(), 12.0, Anchor::BottomRight, style),
+        ]);
+    }
+
+    #[test]
+    fn image_round_trips() {
+        let image = Rc::new(Image::new(2, 1, vec![[255, 0, 0, 255], [0, 0, 255, 128]]));
+        let loaded: Shape = load(&save(&Shape::Image(image.clone(), frame(), 0.5))).unwrap();
+        match loaded {
+            Shape::Image(loaded, loaded_frame, opacity) => {
+                assert_eq!((loaded_frame, opacity), (frame(), 0.5));
+                assert_eq!((loaded.width(), loaded.height(), loaded.to_rgba()),
+                           (image.width(), image.height(), image.to_rgba()));
+            }
+            _ => panic!("{:?}", loaded),
+        }
+    }
+
+    #[test]
+    fn painters_round_trip() {
+        let mut painter = Painter::new(frame());
+        painter.render_circle(&Vector::new(0.5, 0.5), 0.3, &Style::fill(Colour::YELLOW));
+        round_trip(painter.clone());
+        round_trip(painter.clone().clipped());
+        let composite = CompositePainter::new(vec![painter.clone(), painter.right().clipped()])
+            .with_frame(frame().below());
+        round_trip(composite);
+    }
+
+    #[test]
+    fn style_properties_are_optional() {
+        let style: Style = load("(sicp-picture 1 (style (stroke (rgba 0 0 1 1)) (cap round)))").unwrap();
+        assert_eq!(style, Style::stroke(1.0, Colour::BLUE).with_cap(LineCap::Round));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| load::<Vec<Segment>>(text).unwrap_err().to_string();
+        assert_eq!(error("(sicp-picture 2 ())"), "unsupported picture file version 2");
+        assert_eq!(error("((segment (0 0) (1 1)))"), "not a picture file: expected (sicp-picture 1 ...)");
+        assert_eq!(error("(sicp-picture 1 ((segment (0 0) 1)))"), "expected a vector (x y), found 1");
+        assert_eq!(error("(sicp-picture 1 ((segment (0 0) (1 1))"), "line 1: missing )");
+        assert_eq!(load::<Style>("(sicp-picture 1 (style (colour red)))").unwrap_err().to_string(),
+                   "unknown style property colour");
+    }
+
+    #[test]
+    fn files() {
+        let path = std::env::temp_dir().join(format!("sicp-picture-{}.sexp", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_file(path, &frame()).unwrap();
+        assert_eq!(read_file::<Frame>(path).unwrap(), frame());
+        fs::remove_file(path).unwrap();
+        assert!(read_file::<Frame>(path).is_err());
+    }
+}
@@ -0,0 +1,515 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::colour::{to_byte, Colour};
+use crate::picture_language::coordinates::CoordinateSystem;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
+use crate::picture_language::renderer::{self, Renderer};
+use crate::picture_language::shape::{ellipse_points, Shape};
+use crate::picture_language::stroke;
+use crate::picture_language::style::Style;
+use crate::picture_language::transform::Transform;
+use crate::picture_language::vector::Vector;
+
+// Each pixel is sampled on a SAMPLES x SAMPLES grid to anti-alias edges
+const SAMPLES: usize = 4;
+
+pub fn write_png(path: &str, shapes: &[Shape], width: u32, height: u32, coordinates: CoordinateSystem)
+                 -> std::io::Result<()> {
+    to_canvas(shapes, width, height, coordinates).write_png(path)
+}
+
+pub fn to_canvas(shapes: &[Shape], width: u32, height: u32, coordinates: CoordinateSystem) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    canvas.clear(Colour::WHITE);
+    renderer::render_shapes(&coordinates.shapes_to_device(shapes, height as f32), &mut canvas);
+    canvas
+}
+
+// A CPU software rasterizer, so pictures can be rendered without a window or GPU.
+// Pixels are held as premultiplied RGBA.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Canvas { width, height, pixels: vec![[0.0; 4]; (width * height) as usize] }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        unpremultiply(&self.pixels[(y * self.width + x) as usize])
+    }
+
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(unpremultiply).collect()
+    }
+
+    pub fn write_png(&self, path: &str) -> std::io::Result<()> {
+        self.encode_png(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn encode_png<W: Write>(&self, w: W) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba())?;
+        Ok(())
+    }
+
+    // Blends colour into every pixel within [min, max] in proportion to the samples that are inside
+    fn fill<F: Fn(Vector) -> bool>(&mut self, min: Vector, max: Vector, colour: &Colour, inside: F) {
+        let x_range = self.clip(min.x, max.x, self.width);
+        let y_range = self.clip(min.y, max.y, self.height);
+        for y in y_range {
+            for x in x_range.clone() {
+                let covered = (0..SAMPLES * SAMPLES)
+                    .filter(|i| inside(sample_point(x, y, *i)))
+                    .count();
+                if covered > 0 {
+                    let coverage = covered as f32 / (SAMPLES * SAMPLES) as f32;
+                    self.blend(x, y, &premultiply(colour, coverage));
+                }
+            }
+        }
+    }
+
+    // Blends colour once into the union of the polygons, however many of them overlap
+    fn fill_union(&mut self, polygons: &[Vec<Vector>], colour: &Colour) {
+        let Some(Bounds { min, max }) = Bounds::from_points(&polygons.concat()) else { return };
+        let x_range = self.clip(min.x, max.x, self.width);
+        let y_range = self.clip(min.y, max.y, self.height);
+        let columns = x_range.len();
+        // One bit per sample
+        let mut masks = vec![0u16; columns * y_range.len()];
+        for polygon in polygons {
+            let Bounds { min, max } = Bounds::from_points(polygon).unwrap();
+            for y in self.clip(min.y, max.y, self.height) {
+                for x in self.clip(min.x, max.x, self.width) {
+                    let mask = &mut masks[(y - y_range.start) as usize * columns + (x - x_range.start) as usize];
+                    (0..SAMPLES * SAMPLES)
+                        .filter(|i| winding_number(polygon, sample_point(x, y, *i)) != 0)
+                        .for_each(|i| *mask |= 1 << i);
+                }
+            }
+        }
+        for (i, mask) in masks.iter().enumerate().filter(|(_, mask)| **mask != 0) {
+            let coverage = mask.count_ones() as f32 / (SAMPLES * SAMPLES) as f32;
+            self.blend(x_range.start + (i % columns) as u32, y_range.start + (i / columns) as u32,
+                       &premultiply(colour, coverage));
+        }
+    }
+
+    fn clip(&self, min: f32, max: f32, limit: u32) -> std::ops::Range<u32> {
+        let from = min.floor().max(0.0) as u32;
+        let to = (max.ceil().max(0.0) as u32).min(limit);
+        from..to.max(from)
+    }
+
+    fn blend(&mut self, x: u32, y: u32, source: &[f32; 4]) {
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        let remaining = 1.0 - source[3];
+        (0..4).for_each(|i| pixel[i] = source[i] + pixel[i] * remaining);
+    }
+}
+
+impl Renderer for Canvas {
+    fn clear(&mut self, colour: Colour) {
+        let pixel = premultiply(&colour, 1.0);
+        self.pixels.iter_mut().for_each(|p| *p = pixel);
+    }
+
+    fn draw_polyline(&mut self, points: &[Vector], closed: bool, style: &Style) {
+        if let Some(colour) = style.stroke_colour() {
+            self.fill_union(&stroke::stroke(points, closed, style), &colour);
+        }
+    }
+
+    // Non-zero winding, so self-intersecting outlines are filled throughout
+    fn draw_polygon(&mut self, points: &[Vector], style: &Style) {
+        if let (Some(colour), Some(Bounds { min, max })) = (&style.fill_colour(), Bounds::from_points(points)) {
+            self.fill(min, max, colour, |p| winding_number(points, p) != 0);
+        }
+        renderer::stroke_outline(self, points, style);
+    }
+
+    fn draw_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        if let Some(colour) = &style.fill_colour() {
+            let min = Vector::new(centre.x - radius, centre.y - radius);
+            let max = Vector::new(centre.x + radius, centre.y + radius);
+            self.fill(min, max, colour, |p| {
+                let offset = p - *centre;
+                offset.x * offset.x + offset.y * offset.y <= radius * radius
+            });
+        }
+        let outline = ellipse_points(centre, Vector::new(radius, 0.0), Vector::new(0.0, radius));
+        renderer::stroke_outline(self, &outline, style);
+    }
+
+    fn draw_ellipse(&mut self, centre: &Vector, axis1: &Vector, axis2: &Vector, style: &Style) {
+        let determinant = axis1.x * axis2.y - axis1.y * axis2.x;
+        if let (Some(colour), false) = (&style.fill_colour(), determinant == 0.0) {
+            let extent = Vector::new(axis1.x.hypot(axis2.x), axis1.y.hypot(axis2.y));
+            self.fill(*centre - extent, *centre + extent, colour, |p| {
+                // Solve p - centre = axis1 * u + axis2 * v
+                let offset = p - *centre;
+                let u = (offset.x * axis2.y - offset.y * axis2.x) / determinant;
+                let v = (axis1.x * offset.y - axis1.y * offset.x) / determinant;
+                u * u + v * v <= 1.0
+            });
+        }
+        renderer::stroke_outline(self, &ellipse_points(centre, *axis1, *axis2), style);
+    }
+
+    // Each sample is looked up in the image with bilinear filtering, so the image is smooth
+    // when enlarged and averaged over a pixel when shrunk
+    fn draw_image(&mut self, image: &Image, frame: &Frame, opacity: f32) {
+        let Some(to_unit) = Transform::from(*frame).inverse() else { return };
+        if image.width() == 0 || image.height() == 0 {
+            return;
+        }
+        let Bounds { min, max } = frame.bounds();
+        for y in self.clip(min.y, max.y, self.height) {
+            for x in self.clip(min.x, max.x, self.width) {
+                let mut total = [0.0; 4];
+                for i in 0..SAMPLES * SAMPLES {
+                    let unit = to_unit.apply(sample_point(x, y, i));
+                    if (0.0..1.0).contains(&unit.x) && (0.0..1.0).contains(&unit.y) {
+                        let texel = sample(image, unit);
+                        (0..4).for_each(|c| total[c] += texel[c]);
+                    }
+                }
+                if total[3] > 0.0 {
+                    self.blend(x, y, &total.map(|c| c * opacity / (SAMPLES * SAMPLES) as f32));
+                }
+            }
+        }
+    }
+}
+
+// Premultiplied, interpolated between the four pixel centres around the unit-square point
+fn sample(image: &Image, unit: Vector) -> [f32; 4] {
+    let x = unit.x * image.width() as f32 - 0.5;
+    let y = unit.y * image.height() as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let texel = |dx: f32, dy: f32, weight: f32| {
+        let px = (x0 + dx).clamp(0.0, image.width() as f32 - 1.0) as u32;
+        let py = (y0 + dy).clamp(0.0, image.height() as f32 - 1.0) as u32;
+        premultiply(&image.colour(px, py), weight)
+    };
+    let corners = [texel(0.0, 0.0, (1.0 - tx) * (1.0 - ty)), texel(1.0, 0.0, tx * (1.0 - ty)),
+                   texel(0.0, 1.0, (1.0 - tx) * ty), texel(1.0, 1.0, tx * ty)];
+    [0, 1, 2, 3].map(|c| corners.iter().map(|corner| corner[c]).sum())
+}
+
+// How many times the closed outline winds anticlockwise around p
+fn winding_number(points: &[Vector], p: Vector) -> i32 {
+    points.iter().zip(points.iter().cycle().skip(1))
+        .map(|(from, to)| {
+            let side = (to.x - from.x) * (p.y - from.y) - (p.x - from.x) * (to.y - from.y);
+            if from.y <= p.y && to.y > p.y && side > 0.0 {
+                1
+            } else if from.y > p.y && to.y <= p.y && side < 0.0 {
+                -1
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+fn sample_point(x: u32, y: u32, i: usize) -> Vector {
+    let step = 1.0 / SAMPLES as f32;
+    Vector::new(x as f32 + step * ((i % SAMPLES) as f32 + 0.5),
+                y as f32 + step * ((i / SAMPLES) as f32 + 0.5))
+}
+
+fn premultiply(colour: &Colour, coverage: f32) -> [f32; 4] {
+    let alpha = colour.a * coverage;
+    [colour.r * alpha, colour.g * alpha, colour.b * alpha, alpha]
+}
+
+fn unpremultiply(pixel: &[f32; 4]) -> [u8; 4] {
+    let alpha = pixel[3];
+    let channel = |c: f32| if alpha > 0.0 { to_byte(c / alpha) } else { 0 };
+    [channel(pixel[0]), channel(pixel[1]), channel(pixel[2]), to_byte(alpha)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::segment::Segment;
+    use crate::picture_language::shape::Anchor;
+    use crate::picture_language::style::{LineCap, LineJoin};
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(20, 20);
+        canvas.clear(Colour::WHITE);
+        canvas
+    }
+
+    #[test]
+    fn clear() {
+        let canvas = canvas();
+        assert_eq!(canvas.pixel(0, 0), WHITE);
+        assert_eq!(canvas.pixel(19, 19), WHITE);
+    }
+
+    #[test]
+    fn line() {
+        let mut canvas = canvas();
+        let line = Shape::new_line(
+            Segment::new(Vector::new(2.0, 10.0), Vector::new(18.0, 10.0)), Style::stroke(2.0, Colour::RED));
+        renderer::render_shape(&line, &mut canvas);
+        assert_eq!(canvas.pixel(10, 9), RED);
+        assert_eq!(canvas.pixel(10, 10), RED);
+        assert_eq!(canvas.pixel(10, 12), WHITE);
+        assert_eq!(canvas.pixel(1, 10), WHITE);
+        assert_eq!(canvas.pixel(19, 10), WHITE);
+    }
+
+    #[test]
+    fn line_is_anti_aliased() {
+        let mut canvas = canvas();
+        canvas.draw_line(&Segment::new(Vector::new(2.0, 10.0), Vector::new(18.0, 10.0)),
+                         &Style::stroke(1.0, Colour::BLACK));
+        let edge = canvas.pixel(10, 9);
+        assert_eq!(edge[0], edge[1]);
+        assert!(edge[0] > 0 && edge[0] < 255);
+    }
+
+    #[test]
+    fn circle() {
+        let mut canvas = canvas();
+        let circle = Shape::new_circle(&Vector::new(10.0, 10.0), 5.0, Style::fill(Colour::RED));
+        renderer::render_shape(&circle, &mut canvas);
+        assert_eq!(canvas.pixel(10, 10), RED);
+        assert_eq!(canvas.pixel(6, 10), RED);
+        assert_eq!(canvas.pixel(10, 3), WHITE);
+        assert_eq!(canvas.pixel(14, 14), WHITE);
+    }
+
+    #[test]
+    fn ellipse() {
+        let mut canvas = canvas();
+        canvas.draw_ellipse(&Vector::new(10.0, 10.0), &Vector::new(8.0, 0.0), &Vector::new(0.0, 3.0),
+                            &Style::fill(Colour::RED));
+        assert_eq!(canvas.pixel(10, 10), RED);
+        assert_eq!(canvas.pixel(4, 10), RED);
+        assert_eq!(canvas.pixel(10, 6), WHITE);
+        assert_eq!(canvas.pixel(19, 10), WHITE);
+    }
+
+    #[test]
+    fn skewed_ellipse() {
+        let mut canvas = canvas();
+        canvas.draw_ellipse(&Vector::new(10.0, 10.0), &Vector::new(3.0, 0.0), &Vector::new(6.0, 6.0),
+                            &Style::fill(Colour::RED));
+        assert_eq!(canvas.pixel(14, 14), RED);
+        assert_eq!(canvas.pixel(5, 5), RED);
+        assert_eq!(canvas.pixel(15, 5), WHITE);
+    }
+
+    #[test]
+    fn poly_line_joins() {
+        let corner = vec![Vector::new(2.0, 10.0), Vector::new(10.0, 10.0), Vector::new(10.0, 18.0)];
+        let mut mitered = canvas();
+        mitered.draw_polyline(&corner, false, &Style::stroke(4.0, Colour::RED));
+        assert_eq!(mitered.pixel(11, 8), RED);
+        let mut bevelled = canvas();
+        bevelled.draw_polyline(&corner, false, &Style::stroke(4.0, Colour::RED).with_join(LineJoin::Bevel));
+        assert_eq!(bevelled.pixel(11, 8), WHITE);
+    }
+
+    #[test]
+    fn poly_line_caps() {
+        let line = vec![Vector::new(5.0, 10.0), Vector::new(15.0, 10.0)];
+        let mut canvas = canvas();
+        canvas.draw_polyline(&line, false, &Style::stroke(4.0, Colour::RED));
+        assert_eq!(canvas.pixel(3, 10), WHITE);
+        canvas.draw_polyline(&line, false, &Style::stroke(4.0, Colour::RED).with_cap(LineCap::Square));
+        assert_eq!(canvas.pixel(3, 10), RED);
+    }
+
+    #[test]
+    fn translucent_poly_line_blends_once() {
+        let mut canvas = canvas();
+        let corner = vec![Vector::new(2.0, 10.0), Vector::new(10.0, 10.0), Vector::new(10.0, 18.0)];
+        let style = Style::stroke(4.0, Colour::from_rgba(0.0, 0.0, 0.0, 0.5)).with_join(LineJoin::Round);
+        canvas.draw_polyline(&corner, false, &style);
+        assert_eq!(canvas.pixel(9, 10), [128, 128, 128, 255]);
+        assert_eq!(canvas.pixel(5, 10), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn dashed_line() {
+        let mut canvas = canvas();
+        let dashed = Style::stroke(2.0, Colour::RED).with_dash(vec![4.0, 4.0]);
+        canvas.draw_line(&Segment::new(Vector::new(0.0, 10.0), Vector::new(20.0, 10.0)), &dashed);
+        assert_eq!(canvas.pixel(2, 10), RED);
+        assert_eq!(canvas.pixel(6, 10), WHITE);
+        assert_eq!(canvas.pixel(10, 10), RED);
+    }
+
+    #[test]
+    fn opacity() {
+        let mut canvas = canvas();
+        canvas.draw_rect(&Vector::zero(), &Vector::new(20.0, 20.0),
+                         &Style::fill(Colour::BLACK).with_opacity(0.5));
+        assert_eq!(canvas.pixel(10, 10), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn text_uses_the_bitmap_font() {
+        let mut canvas = canvas();
+        canvas.draw_text("I", &Vector::new(10.0, 10.0), 16.0, Anchor::Centre, &Style::fill(Colour::RED));
+        // The vertical stroke of the I is two pixels wide, in the middle of the glyph
+        assert_eq!(canvas.pixel(9, 10), RED);
+        assert_eq!(canvas.pixel(5, 10), WHITE);
+    }
+
+    #[test]
+    fn outlined_circle() {
+        let mut canvas = canvas();
+        canvas.draw_circle(&Vector::new(10.0, 10.0), 6.0, &Style::stroke(2.0, Colour::RED));
+        assert_eq!(canvas.pixel(15, 10), RED);
+        assert_eq!(canvas.pixel(10, 10), WHITE);
+    }
+
+    #[test]
+    fn polygon() {
+        let mut canvas = canvas();
+        let triangle = Shape::new_polygon(
+            vec![Vector::new(2.0, 2.0), Vector::new(18.0, 2.0), Vector::new(2.0, 18.0)],
+            Style::fill(Colour::RED));
+        renderer::render_shape(&triangle, &mut canvas);
+        assert_eq!(canvas.pixel(4, 4), RED);
+        assert_eq!(canvas.pixel(8, 8), RED);
+        assert_eq!(canvas.pixel(12, 12), WHITE);
+        assert_eq!(canvas.pixel(1, 1), WHITE);
+    }
+
+    #[test]
+    fn self_intersecting_polygon_fills_by_winding() {
+        // A pentagram: its centre is wound twice, so is filled under non-zero winding
+        let star: Vec<Vector> = (0..5)
+            .map(|i| {
+                let angle = (i * 2) as f32 * std::f32::consts::TAU / 5.0;
+                Vector::new(10.0 + 9.0 * angle.sin(), 10.0 - 9.0 * angle.cos())
+            })
+            .collect();
+        assert_eq!(winding_number(&star, Vector::new(10.0, 10.0)).abs(), 2);
+        let mut canvas = canvas();
+        canvas.draw_polygon(&star, &Style::fill(Colour::RED));
+        assert_eq!(canvas.pixel(10, 10), RED);
+    }
+
+    #[test]
+    fn rect_with_fill_and_stroke() {
+        let mut canvas = canvas();
+        canvas.draw_rect(&Vector::new(4.0, 4.0), &Vector::new(16.0, 16.0),
+                         &Style::fill_and_stroke(Colour::BLUE, 2.0, Colour::RED));
+        assert_eq!(canvas.pixel(10, 10), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(10, 4), RED);
+        assert_eq!(canvas.pixel(10, 2), WHITE);
+    }
+
+    #[test]
+    fn shapes_off_canvas_are_clipped() {
+        let mut canvas = canvas();
+        canvas.draw_circle(&Vector::new(-2.0, 22.0), 5.0, &Style::fill(Colour::RED));
+        canvas.draw_line(&Segment::new(Vector::new(-10.0, -10.0), Vector::new(40.0, -10.0)),
+                         &Style::stroke(3.0, Colour::RED));
+        assert_eq!(canvas.pixel(0, 19), RED);
+        assert_eq!(canvas.pixel(10, 0), WHITE);
+    }
+
+    #[test]
+    fn transparent_colour_blends() {
+        let mut canvas = canvas();
+        canvas.draw_circle(&Vector::new(10.0, 10.0), 5.0, &Style::fill(Colour::from_rgba(0.0, 0.0, 0.0, 0.5)));
+        assert_eq!(canvas.pixel(10, 10), [128, 128, 128, 255]);
+    }
+
+    fn quadrants() -> Image {
+        Image::new(2, 2, vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [0, 0, 0, 0]])
+    }
+
+    #[test]
+    fn image_fills_its_frame() {
+        let mut canvas = canvas();
+        let frame = Frame::new(Vector::zero(), Vector::new(20.0, 0.0), Vector::new(0.0, 20.0));
+        canvas.draw_image(&quadrants(), &frame, 1.0);
+        assert_eq!(canvas.pixel(2, 2), RED);
+        assert_eq!(canvas.pixel(17, 2), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(2, 17), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(17, 17), WHITE);
+        // Smoothed between pixel centres
+        let middle = canvas.pixel(10, 2);
+        assert!(middle[0] > 0 && middle[1] > 0, "{:?}", middle);
+    }
+
+    #[test]
+    fn flipped_image() {
+        let mut canvas = canvas();
+        canvas.draw_image(&quadrants(), &Frame::new(Vector::new(10.0, 0.0), Vector::new(-10.0, 0.0),
+                                                   Vector::new(0.0, 10.0)), 1.0);
+        assert_eq!(canvas.pixel(1, 1), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(8, 1), RED);
+        assert_eq!(canvas.pixel(15, 15), WHITE);
+        assert_eq!(canvas.pixel(11, 1), WHITE);
+    }
+
+    #[test]
+    fn image_in_a_flat_frame() {
+        let mut canvas = canvas();
+        let frame = Frame::new(Vector::zero(), Vector::new(20.0, 20.0), Vector::new(10.0, 10.0));
+        canvas.draw_image(&quadrants(), &frame, 1.0);
+        assert_eq!(canvas, self::canvas());
+    }
+
+    #[test]
+    fn translucent_image() {
+        let mut canvas = canvas();
+        let frame = Frame::new(Vector::zero(), Vector::new(20.0, 0.0), Vector::new(0.0, 20.0));
+        canvas.draw_image(&quadrants(), &frame, 0.5);
+        assert_eq!(canvas.pixel(2, 2), [255, 128, 128, 255]);
+        assert_eq!(canvas.pixel(17, 17), WHITE);
+    }
+
+    #[test]
+    fn image_with_y_up() {
+        // The first row stays at the top
+        let image = std::rc::Rc::new(quadrants());
+        let frame = Frame::new(Vector::zero(), Vector::new(20.0, 0.0), Vector::new(0.0, 20.0));
+        let canvas = to_canvas(&[Shape::new_image(&image, frame)], 20, 20, CoordinateSystem::YUp);
+        assert_eq!(canvas.pixel(2, 2), RED);
+        assert_eq!(canvas.pixel(17, 2), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(2, 17), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(17, 17), WHITE);
+    }
+
+    #[test]
+    fn encode_png() {
+        let mut bytes = vec![];
+        canvas().encode_png(&mut bytes).unwrap();
+        assert_eq!(&bytes[..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+    }
+}
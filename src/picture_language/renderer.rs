@@ -0,0 +1,356 @@
+use crate::picture_language::colour::Colour;
+use crate::picture_language::segment::Segment;
+use crate::picture_language::bitmap_font;
+use crate::picture_language::curve::{self, Curve};
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
+use crate::picture_language::shape::{ellipse_points, Anchor, Shape};
+use crate::picture_language::stroke;
+use crate::picture_language::style::Style;
+use crate::picture_language::vector::Vector;
+
+// A drawing backend. Shapes are only ever taken apart in render_shape(), so a new
+// Shape variant is added there and to this trait, rather than in every backend's caller.
+// Only clear and draw_polygon are needed: everything else can be made of filled polygons.
+pub trait Renderer {
+    fn clear(&mut self, colour: Colour);
+
+    // Filled with the style's fill, then outlined with its stroke
+    fn draw_polygon(&mut self, points: &[Vector], style: &Style);
+
+    fn draw_line(&mut self, segment: &Segment, style: &Style) {
+        self.draw_polyline(&[segment.start, segment.end], false, style);
+    }
+
+    // Stroked, dashed, joined and capped as in the style; closed paths also join back to the start.
+    // The fill is ignored. The stroke's pieces are cut apart where they overlap, so that a
+    // translucent stroke is blended once everywhere.
+    fn draw_polyline(&mut self, points: &[Vector], closed: bool, style: &Style) {
+        if let Some(colour) = style.stroke_colour() {
+            stroke::disjoint(&stroke::stroke(points, closed, style)).iter()
+                .for_each(|piece| self.draw_polygon(piece, &Style::fill(colour)));
+        }
+    }
+
+    // Flattened into a polyline. The fill is drawn as if the path were closed, as in SVG.
+    fn draw_path(&mut self, curves: &[Curve], closed: bool, style: &Style) {
+        let points = curve::flatten_path(curves, curve::TOLERANCE);
+        if style.fill.is_some() {
+            self.draw_polygon(&points, &Style { stroke: None, ..style.clone() });
+        }
+        self.draw_polyline(&points, closed, style);
+    }
+
+    fn draw_rect(&mut self, min: &Vector, max: &Vector, style: &Style) {
+        self.draw_polygon(&[*min, Vector::new(max.x, min.y), *max, Vector::new(min.x, max.y)], style);
+    }
+
+    fn draw_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        self.draw_ellipse(centre, &Vector::new(radius, 0.0), &Vector::new(0.0, radius), style);
+    }
+
+    // Axes as in Shape::Ellipse
+    fn draw_ellipse(&mut self, centre: &Vector, axis1: &Vector, axis2: &Vector, style: &Style) {
+        self.draw_polygon(&ellipse_points(centre, *axis1, *axis2), style);
+    }
+
+    // A single line of text, size high, in the built-in bitmap font
+    fn draw_text(&mut self, text: &str, position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        bitmap_font::draw_text(self, text, position, size, anchor, style);
+    }
+
+    // As in Shape::Image
+    fn draw_image(&mut self, image: &Image, frame: &Frame, opacity: f32) {
+        image_pixels(self, image, frame, opacity);
+    }
+}
+
+pub fn render_shapes<R: Renderer + ?Sized>(shapes: &[Shape], renderer: &mut R) {
+    shapes.iter().for_each(|shape| render_shape(shape, renderer));
+}
+
+pub fn render_shape<R: Renderer + ?Sized>(shape: &Shape, renderer: &mut R) {
+    match shape {
+        Shape::Line(segment, style) =>
+            renderer.draw_line(segment, style),
+        Shape::PolyLine(points, closed, style) =>
+            renderer.draw_polyline(points, *closed, style),
+        Shape::Path(curves, closed, style) =>
+            renderer.draw_path(curves, *closed, style),
+        Shape::Circle(centre, radius, style) =>
+            renderer.draw_circle(centre, *radius, style),
+        Shape::Ellipse(centre, axis1, axis2, style) =>
+            renderer.draw_ellipse(centre, axis1, axis2, style),
+        Shape::Polygon(points, style) =>
+            renderer.draw_polygon(points, style),
+        Shape::Rect(min, max, style) =>
+            renderer.draw_rect(min, max, style),
+        Shape::Text(text, position, size, anchor, style) =>
+            renderer.draw_text(text, position, *size, *anchor, style),
+        Shape::Image(image, frame, opacity) =>
+            renderer.draw_image(image, frame, *opacity),
+    }
+}
+
+// For backends that fill and outline separately: the outline is a closed polyline
+pub fn stroke_outline<R: Renderer + ?Sized>(renderer: &mut R, points: &[Vector], style: &Style) {
+    if style.stroke.is_some() {
+        renderer.draw_polyline(points, true, style);
+    }
+}
+
+// For backends that cannot draw images: each pixel is a polygon of its own
+pub fn image_pixels<R: Renderer + ?Sized>(renderer: &mut R, image: &Image, frame: &Frame, opacity: f32) {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let colour = image.colour(x, y);
+            if colour.a > 0.0 {
+                let corner = |dx: u32, dy: u32|
+                    frame.map(Vector::new((x + dx) as f32 / width, (y + dy) as f32 / height));
+                renderer.draw_polygon(&[corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)],
+                                      &Style::fill(colour).with_opacity(opacity));
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DrawCall {
+    Clear(Colour),
+    Line(Segment, Style),
+    PolyLine(Vec<Vector>, bool, Style),
+    Path(Vec<Curve>, bool, Style),
+    Circle(Vector, f32, Style),
+    Ellipse(Vector, Vector, Vector, Style),
+    Polygon(Vec<Vector>, Style),
+    Rect(Vector, Vector, Style),
+    Text(String, Vector, f32, Anchor, Style),
+    Image(Image, Frame, f32),
+}
+
+// Records what it was asked to draw, so that tests can check rendering without a window
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RecordingRenderer {
+    pub calls: Vec<DrawCall>,
+}
+
+impl RecordingRenderer {
+    pub fn new() -> Self {
+        RecordingRenderer { calls: vec![] }
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn clear(&mut self, colour: Colour) {
+        self.calls.push(DrawCall::Clear(colour));
+    }
+
+    fn draw_polygon(&mut self, points: &[Vector], style: &Style) {
+        self.calls.push(DrawCall::Polygon(points.to_vec(), style.clone()));
+    }
+
+    fn draw_line(&mut self, segment: &Segment, style: &Style) {
+        self.calls.push(DrawCall::Line(*segment, style.clone()));
+    }
+
+    fn draw_polyline(&mut self, points: &[Vector], closed: bool, style: &Style) {
+        self.calls.push(DrawCall::PolyLine(points.to_vec(), closed, style.clone()));
+    }
+
+    fn draw_path(&mut self, curves: &[Curve], closed: bool, style: &Style) {
+        self.calls.push(DrawCall::Path(curves.to_vec(), closed, style.clone()));
+    }
+
+    fn draw_rect(&mut self, min: &Vector, max: &Vector, style: &Style) {
+        self.calls.push(DrawCall::Rect(*min, *max, style.clone()));
+    }
+
+    fn draw_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        self.calls.push(DrawCall::Circle(*centre, radius, style.clone()));
+    }
+
+    fn draw_ellipse(&mut self, centre: &Vector, axis1: &Vector, axis2: &Vector, style: &Style) {
+        self.calls.push(DrawCall::Ellipse(*centre, *axis1, *axis2, style.clone()));
+    }
+
+    fn draw_text(&mut self, text: &str, position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        self.calls.push(DrawCall::Text(text.to_string(), *position, size, anchor, style.clone()));
+    }
+
+    fn draw_image(&mut self, image: &Image, frame: &Frame, opacity: f32) {
+        self.calls.push(DrawCall::Image(image.clone(), *frame, opacity));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::picture_language::style::{LineCap, LineJoin};
+
+    // Only the required methods, so everything else goes through the defaults
+    struct Polygons {
+        polygons: Vec<(Vec<Vector>, Style)>,
+    }
+
+    impl Polygons {
+        fn new() -> Self {
+            Polygons { polygons: vec![] }
+        }
+
+        fn points(&self) -> Vec<Vec<Vector>> {
+            self.polygons.iter().map(|(points, _)| points.clone()).collect()
+        }
+    }
+
+    impl Renderer for Polygons {
+        fn clear(&mut self, _colour: Colour) {}
+
+        fn draw_polygon(&mut self, points: &[Vector], style: &Style) {
+            if style.fill.is_some() {
+                self.polygons.push((points.to_vec(), style.clone()));
+            }
+            stroke_outline(self, points, style);
+        }
+    }
+
+    #[test]
+    fn dispatches_each_shape() {
+        let segment = Segment::new(Vector::zero(), Vector::one());
+        let image = Rc::new(Image::new(1, 1, vec![[0, 0, 0, 255]]));
+        let frame = Frame::new(Vector::zero(), Vector::new(10.0, 0.0), Vector::new(0.0, 10.0));
+        let shapes = vec![
+            Shape::new_line(segment, Style::stroke(1.0, Colour::RED)),
+            Shape::new_poly_line(vec![Vector::zero(), Vector::one()], false, Style::stroke(2.0, Colour::GREEN)),
+            Shape::new_path(vec![Curve::Line(Vector::zero(), Vector::one())], true, Style::fill(Colour::BLUE)),
+            Shape::new_circle(&Vector::one(), 3.0, Style::fill(Colour::BLUE)),
+            Shape::new_ellipse(&Vector::one(), Vector::new(2.0, 0.0), Vector::new(1.0, 3.0),
+                               Style::fill(Colour::RED)),
+            Shape::new_polygon(vec![Vector::zero(), Vector::one()], Style::stroke(1.0, Colour::RED)),
+            Shape::new_rect(Vector::zero(), Vector::one(), Style::fill(Colour::GREEN)),
+            Shape::new_text("label", Vector::one(), 12.0, Anchor::Left, Style::fill(Colour::BLACK)),
+            Shape::new_image(&image, frame),
+        ];
+        let mut renderer = RecordingRenderer::new();
+        render_shapes(&shapes, &mut renderer);
+        assert_eq!(renderer.calls, vec![
+            DrawCall::Line(segment, Style::stroke(1.0, Colour::RED)),
+            DrawCall::PolyLine(vec![Vector::zero(), Vector::one()], false, Style::stroke(2.0, Colour::GREEN)),
+            DrawCall::Path(vec![Curve::Line(Vector::zero(), Vector::one())], true, Style::fill(Colour::BLUE)),
+            DrawCall::Circle(Vector::one(), 3.0, Style::fill(Colour::BLUE)),
+            DrawCall::Ellipse(Vector::one(), Vector::new(2.0, 0.0), Vector::new(1.0, 3.0),
+                              Style::fill(Colour::RED)),
+            DrawCall::Polygon(vec![Vector::zero(), Vector::one()], Style::stroke(1.0, Colour::RED)),
+            DrawCall::Rect(Vector::zero(), Vector::one(), Style::fill(Colour::GREEN)),
+            DrawCall::Text("label".to_string(), Vector::one(), 12.0, Anchor::Left, Style::fill(Colour::BLACK)),
+            DrawCall::Image((*image).clone(), frame, 1.0),
+        ]);
+    }
+
+    #[test]
+    fn polyline_defaults_to_stroke_pieces() {
+        let points = vec![Vector::new(0.0, 0.0), Vector::new(1.0, 0.0), Vector::new(1.0, 1.0)];
+        let style = Style::stroke(1.0, Colour::BLACK);
+        let mut renderer = Polygons::new();
+        render_shape(&Shape::new_poly_line(points.clone(), false, style.clone()), &mut renderer);
+        assert_eq!(renderer.points(), stroke::disjoint(&stroke::stroke(&points, false, &style)));
+    }
+
+    // The number of polygons with the point inside, for convex polygons either way round
+    fn coverage(polygons: &[Vec<Vector>], p: Vector) -> usize {
+        polygons.iter().filter(|polygon| {
+            let turns: Vec<f32> = (0..polygon.len()).map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+            }).collect();
+            turns.iter().all(|turn| *turn > 0.0) || turns.iter().all(|turn| *turn < 0.0)
+        }).count()
+    }
+
+    #[test]
+    fn translucent_strokes_are_blended_once() {
+        let points = vec![Vector::new(2.0, 2.0), Vector::new(12.0, 2.0), Vector::new(4.0, 8.0)];
+        for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+            let style = Style::stroke(3.0, Colour::BLACK).with_join(join).with_cap(LineCap::Round).with_opacity(0.5);
+            let mut renderer = Polygons::new();
+            renderer.draw_polyline(&points, false, &style);
+            let drawn = renderer.points();
+            let pieces = stroke::stroke(&points, false, &style);
+            for y in 0..160 {
+                for x in 0..160 {
+                    let p = Vector::new(x as f32 / 10.0 + 0.013, y as f32 / 10.0 + 0.017);
+                    let covered = coverage(&drawn, p);
+                    assert!(covered <= 1, "{:?} covered {} times with {:?}", p, covered, join);
+                    assert_eq!(covered == 1, coverage(&pieces, p) > 0, "{:?} with {:?}", p, join);
+                }
+            }
+            assert!(renderer.polygons.iter().all(|(_, style)| style.fill_colour().unwrap().a == 0.5));
+        }
+    }
+
+    #[test]
+    fn line_is_a_two_point_polyline() {
+        let style = Style::stroke(2.0, Colour::BLACK).with_opacity(0.5);
+        let mut renderer = Polygons::new();
+        renderer.draw_line(&Segment::new(Vector::zero(), Vector::new(10.0, 0.0)), &style);
+        assert_eq!(renderer.polygons, vec![(
+            vec![Vector::new(0.0, 1.0), Vector::new(10.0, 1.0), Vector::new(10.0, -1.0), Vector::new(0.0, -1.0)],
+            Style::fill(Colour::from_rgba(0.0, 0.0, 0.0, 0.5)),
+        )]);
+    }
+
+    #[test]
+    fn path_defaults_to_flattened_fill_and_stroke() {
+        let curves = [Curve::Cubic(Vector::zero(), Vector::new(0.0, 100.0), Vector::new(100.0, 100.0),
+                                   Vector::new(100.0, 0.0))];
+        let points = curve::flatten_path(&curves, curve::TOLERANCE);
+        let style = Style::fill_and_stroke(Colour::RED, 2.0, Colour::BLACK);
+        let mut renderer = Polygons::new();
+        renderer.draw_path(&curves, false, &style);
+        assert_eq!(renderer.polygons[0], (points.clone(), Style { stroke: None, ..style.clone() }));
+        assert_eq!(renderer.points()[1..], stroke::disjoint(&stroke::stroke(&points, false, &style))[..]);
+    }
+
+    #[test]
+    fn image_defaults_to_a_polygon_per_pixel() {
+        let image = Image::new(2, 1, vec![[255, 0, 0, 255], [0, 0, 0, 0]]);
+        let frame = Frame::new(Vector::new(10.0, 10.0), Vector::new(20.0, 0.0), Vector::new(0.0, 5.0));
+        let mut renderer = Polygons::new();
+        renderer.draw_image(&image, &frame, 1.0);
+        // The transparent pixel is left out
+        assert_eq!(renderer.polygons, vec![(
+            vec![Vector::new(10.0, 10.0), Vector::new(20.0, 10.0), Vector::new(20.0, 15.0), Vector::new(10.0, 15.0)],
+            Style::fill(Colour::RED),
+        )]);
+    }
+
+    #[test]
+    fn rect_defaults_to_polygon() {
+        let mut renderer = Polygons::new();
+        renderer.draw_rect(&Vector::zero(), &Vector::new(2.0, 1.0), &Style::fill(Colour::RED));
+        assert_eq!(renderer.points(), vec![
+            vec![Vector::zero(), Vector::new(2.0, 0.0), Vector::new(2.0, 1.0), Vector::new(0.0, 1.0)],
+        ]);
+    }
+
+    #[test]
+    fn circle_defaults_to_polygon() {
+        let mut renderer = Polygons::new();
+        renderer.draw_circle(&Vector::zero(), 10.0, &Style::fill(Colour::RED));
+        assert_eq!(renderer.polygons.len(), 1);
+        assert_eq!(renderer.points()[0][0], Vector::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn stroke_outline_closes_the_loop() {
+        let v1 = Vector::new(0.0, 0.0);
+        let v2 = Vector::new(1.0, 0.0);
+        let v3 = Vector::new(1.0, 1.0);
+        let style = Style::fill_and_stroke(Colour::RED, 1.0, Colour::BLACK);
+        let mut renderer = Polygons::new();
+        renderer.draw_polygon(&[v1, v2, v3], &style);
+        let outline = stroke::disjoint(&stroke::stroke(&[v1, v2, v3], true, &style));
+        assert_eq!(renderer.points(), [vec![vec![v1, v2, v3]], outline].concat());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::renderer::{self, Renderer};
+use crate::picture_language::shape::Shape;
+use crate::picture_language::style::Style;
+use crate::picture_language::transform::Transform;
+
+// A picture as a tree. Groups carry a transform and a restyling for everything inside them,
+// and can be hidden. Siblings are drawn in order of their z-index, lowest first, and in the
+// order they were added when their z-indexes are equal; shapes have a z-index of 0.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Shape(Shape),
+    Group(Group),
+}
+
+// Changes to the styles of the shapes in a group: colours replace those the shapes are drawn
+// with, and the opacity multiplies theirs, images' included
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GroupStyle {
+    pub stroke: Option<Colour>,
+    pub fill: Option<Colour>,
+    pub opacity: f32,
+}
+
+impl GroupStyle {
+    pub fn new() -> Self {
+        GroupStyle { stroke: None, fill: None, opacity: 1.0 }
+    }
+
+    pub fn with_stroke(self, colour: Colour) -> Self {
+        GroupStyle { stroke: Some(colour), ..self }
+    }
+
+    pub fn with_fill(self, colour: Colour) -> Self {
+        GroupStyle { fill: Some(colour), ..self }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        GroupStyle { opacity, ..self }
+    }
+
+    // Only colours the style already uses are replaced, so a line does not gain a fill
+    pub fn apply(&self, style: &Style) -> Style {
+        Style {
+            stroke: style.stroke.and(self.stroke).or(style.stroke),
+            fill: style.fill.and(self.fill).or(style.fill),
+            opacity: style.opacity * self.opacity,
+            ..style.clone()
+        }
+    }
+
+    // The outer style applied after this one
+    fn then(&self, outer: &GroupStyle) -> Self {
+        GroupStyle {
+            stroke: outer.stroke.or(self.stroke),
+            fill: outer.fill.or(self.fill),
+            opacity: self.opacity * outer.opacity,
+        }
+    }
+}
+
+impl Default for GroupStyle {
+    fn default() -> Self {
+        GroupStyle::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Group {
+    pub name: String,
+    pub transform: Transform,
+    pub style: GroupStyle,
+    pub z_index: i32,
+    pub visible: bool,
+    children: Vec<Node>,
+}
+
+impl Group {
+    pub fn new(name: &str) -> Self {
+        Group {
+            name: name.to_string(),
+            transform: Transform::IDENTITY,
+            style: GroupStyle::new(),
+            z_index: 0,
+            visible: true,
+            children: vec![],
+        }
+    }
+
+    pub fn with_transform(self, transform: Transform) -> Self {
+        Group { transform, ..self }
+    }
+
+    pub fn with_style(self, style: GroupStyle) -> Self {
+        Group { style, ..self }
+    }
+
+    pub fn with_z_index(self, z_index: i32) -> Self {
+        Group { z_index, ..self }
+    }
+
+    pub fn hidden(self) -> Self {
+        Group { visible: false, ..self }
+    }
+
+    pub fn with_shapes(mut self, shapes: Vec<Shape>) -> Self {
+        self.add_shapes(shapes);
+        self
+    }
+
+    pub fn with_group(mut self, group: Group) -> Self {
+        self.add_group(group);
+        self
+    }
+
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn add_shape(&mut self, shape: Shape) {
+        self.children.push(Node::Shape(shape));
+    }
+
+    // Such as the output of a painter's paint()
+    pub fn add_shapes(&mut self, shapes: Vec<Shape>) {
+        self.children.extend(shapes.into_iter().map(Node::Shape));
+    }
+
+    pub fn add_group(&mut self, group: Group) {
+        self.children.push(Node::Group(group));
+    }
+
+    // Removes the first group with the name, searching depth first
+    pub fn remove_group(&mut self, name: &str) -> Option<Group> {
+        if let Some(i) = self.children.iter().position(|node| matches!(node, Node::Group(g) if g.name == name)) {
+            return match self.children.remove(i) {
+                Node::Group(group) => Some(group),
+                Node::Shape(_) => None,
+            };
+        }
+        self.groups_mut().find_map(|group| group.remove_group(name))
+    }
+
+    // This group or the first group inside it with the name, searching depth first
+    pub fn find(&self, name: &str) -> Option<&Group> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter()
+            .filter_map(|node| match node {
+                Node::Group(group) => group.find(name),
+                Node::Shape(_) => None,
+            })
+            .next()
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Group> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.groups_mut().find_map(|group| group.find_mut(name))
+    }
+
+    fn groups_mut(&mut self) -> impl Iterator<Item = &mut Group> {
+        self.children.iter_mut().filter_map(|node| match node {
+            Node::Group(group) => Some(group),
+            Node::Shape(_) => None,
+        })
+    }
+
+    // Returns false if there is no group with the name
+    pub fn set_visible(&mut self, name: &str, visible: bool) -> bool {
+        self.find_mut(name).map(|group| group.visible = visible).is_some()
+    }
+
+    // The visible shapes in drawing order, with the transforms and styles of the groups they are in
+    pub fn shapes(&self) -> Vec<Shape> {
+        let mut shapes = vec![];
+        self.collect(&Transform::IDENTITY, &GroupStyle::new(), &mut shapes);
+        shapes
+    }
+
+    fn collect(&self, outer_transform: &Transform, outer_style: &GroupStyle, shapes: &mut Vec<Shape>) {
+        if !self.visible {
+            return;
+        }
+        let transform = self.transform.then(outer_transform);
+        let style = self.style.then(outer_style);
+        let mut children: Vec<&Node> = self.children.iter().collect();
+        children.sort_by_key(|node| match node {
+            Node::Group(group) => group.z_index,
+            Node::Shape(_) => 0,
+        });
+        for node in children {
+            match node {
+                Node::Shape(shape) => shapes.push(restyle(&shape.transformed(&transform), &style)),
+                Node::Group(group) => group.collect(&transform, &style, shapes),
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.shapes().iter().filter_map(Shape::bounds))
+    }
+
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &mut R) {
+        renderer::render_shapes(&self.shapes(), renderer);
+    }
+}
+
+fn restyle(shape: &Shape, group_style: &GroupStyle) -> Shape {
+    let restyled = |style: &Style| group_style.apply(style);
+    match shape {
+        Shape::Line(segment, style) => Shape::Line(*segment, restyled(style)),
+        Shape::PolyLine(points, closed, style) => Shape::PolyLine(points.clone(), *closed, restyled(style)),
+        Shape::Path(curves, closed, style) => Shape::Path(curves.clone(), *closed, restyled(style)),
+        Shape::Circle(centre, radius, style) => Shape::Circle(*centre, *radius, restyled(style)),
+        Shape::Ellipse(centre, axis1, axis2, style) => Shape::Ellipse(*centre, *axis1, *axis2, restyled(style)),
+        Shape::Polygon(points, style) => Shape::Polygon(points.clone(), restyled(style)),
+        Shape::Rect(min, max, style) => Shape::Rect(*min, *max, restyled(style)),
+        Shape::Text(text, position, size, anchor, style) =>
+            Shape::Text(text.clone(), *position, *size, *anchor, restyled(style)),
+        Shape::Image(image, frame, opacity) => Shape::Image(image.clone(), *frame, opacity * group_style.opacity),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::picture_language::frame::Frame;
+    use crate::picture_language::image::Image;
+    use crate::picture_language::renderer::{DrawCall, RecordingRenderer};
+    use crate::picture_language::vector::Vector;
+
+    fn dot(x: f32) -> Shape {
+        Shape::new_circle(&Vector::new(x, 0.0), 1.0, Style::fill(Colour::RED))
+    }
+
+    #[test]
+    fn z_index_orders_siblings() {
+        let scene = Group::new("scene")
+            .with_group(Group::new("front").with_z_index(1).with_shapes(vec![dot(1.0)]))
+            .with_shapes(vec![dot(2.0)])
+            .with_group(Group::new("back").with_z_index(-1).with_shapes(vec![dot(3.0)]))
+            .with_group(Group::new("also front").with_z_index(1).with_shapes(vec![dot(4.0)]));
+        assert_eq!(scene.shapes(), vec![dot(3.0), dot(2.0), dot(1.0), dot(4.0)]);
+    }
+
+    #[test]
+    fn transforms_nest() {
+        let inner = Group::new("inner").with_transform(Transform::scale(2.0, 2.0)).with_shapes(vec![dot(1.0)]);
+        let outer = Group::new("outer").with_transform(Transform::translate(Vector::new(10.0, 0.0))).with_group(inner);
+        assert_eq!(outer.shapes(), vec![
+            Shape::new_circle(&Vector::new(12.0, 0.0), 2.0, Style::fill(Colour::RED)),
+        ]);
+    }
+
+    #[test]
+    fn styles_nest() {
+        let line = Shape::new_poly_line(vec![Vector::zero(), Vector::one()], false, Style::stroke(1.0, Colour::BLACK));
+        let inner = Group::new("inner").with_style(GroupStyle::new().with_stroke(Colour::BLUE).with_opacity(0.5))
+            .with_shapes(vec![line, dot(0.0)]);
+        let outer = Group::new("outer").with_style(GroupStyle::new().with_fill(Colour::GREEN).with_opacity(0.5))
+            .with_group(inner);
+        assert_eq!(outer.shapes(), vec![
+            Shape::new_poly_line(vec![Vector::zero(), Vector::one()], false,
+                                 Style::stroke(1.0, Colour::BLUE).with_opacity(0.25)),
+            Shape::new_circle(&Vector::zero(), 1.0, Style::fill(Colour::GREEN).with_opacity(0.25)),
+        ]);
+    }
+
+    #[test]
+    fn group_opacity_fades_images() {
+        let image = Rc::new(Image::new(1, 1, vec![[255, 0, 0, 255]]));
+        let frame = Frame::new(Vector::zero(), Vector::one(), Vector::new(0.0, 1.0));
+        let inner = Group::new("inner").with_style(GroupStyle::new().with_opacity(0.5))
+            .with_shapes(vec![Shape::new_image(&image, frame)]);
+        let outer = Group::new("outer").with_style(GroupStyle::new().with_opacity(0.5)).with_group(inner);
+        assert_eq!(outer.shapes(), vec![Shape::Image(image, frame, 0.25)]);
+    }
+
+    #[test]
+    fn show_and_hide() {
+        let mut scene = Group::new("scene")
+            .with_group(Group::new("grid").hidden().with_shapes(vec![dot(1.0)]))
+            .with_shapes(vec![dot(2.0)]);
+        assert_eq!(scene.shapes(), vec![dot(2.0)]);
+        assert!(scene.set_visible("grid", true));
+        assert_eq!(scene.shapes(), vec![dot(1.0), dot(2.0)]);
+        assert!(!scene.set_visible("missing", true));
+    }
+
+    #[test]
+    fn find_and_remove() {
+        let mut scene = Group::new("scene")
+            .with_group(Group::new("a").with_group(Group::new("b").with_shapes(vec![dot(1.0)])));
+        assert_eq!(scene.find("b").map(|b| b.children().len()), Some(1));
+        scene.find_mut("b").unwrap().add_shape(dot(2.0));
+        assert_eq!(scene.shapes(), vec![dot(1.0), dot(2.0)]);
+        assert_eq!(scene.remove_group("b").map(|b| b.name), Some("b".to_string()));
+        assert_eq!(scene.find("b"), None);
+        assert_eq!(scene.shapes(), vec![]);
+    }
+
+    #[test]
+    fn render_and_bounds() {
+        let scene = Group::new("scene").with_shapes(vec![dot(0.0), dot(10.0)]);
+        assert_eq!(scene.bounds(), Some(Bounds::new(Vector::new(-1.0, -1.0), Vector::new(11.0, 1.0))));
+        let mut renderer = RecordingRenderer::new();
+        scene.render(&mut renderer);
+        assert_eq!(renderer.calls, vec![
+            DrawCall::Circle(Vector::zero(), 1.0, Style::fill(Colour::RED)),
+            DrawCall::Circle(Vector::new(10.0, 0.0), 1.0, Style::fill(Colour::RED)),
+        ]);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::picture_language::frame::Frame;
 use crate::picture_language::vector::Vector;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -20,6 +21,85 @@ impl Segment {
             segment
         }).collect()
     }
+
+    pub fn length(&self) -> f32 {
+        (self.end - self.start).length()
+    }
+
+    pub fn midpoint(&self) -> Vector {
+        self.point_at(0.5)
+    }
+
+    // t runs from 0.0 at the start to 1.0 at the end
+    pub fn point_at(&self, t: f32) -> Vector {
+        self.start + (self.end - self.start).scale(t)
+    }
+
+    // Where the two segments cross, including at their ends. Parallel segments have no
+    // single crossing point, so overlapping ones give None too.
+    pub fn intersection(&self, other: &Segment) -> Option<Vector> {
+        let (d1, d2) = (self.end - self.start, other.end - other.start);
+        let denominator = d1.cross(d2);
+        if denominator == 0.0 {
+            return None;
+        }
+        let offset = other.start - self.start;
+        let t = offset.cross(d2) / denominator;
+        let u = offset.cross(d1) / denominator;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(self.point_at(t))
+        } else {
+            None
+        }
+    }
+
+    // To the nearest point of the segment, which may be one of its ends
+    pub fn distance_to(&self, p: Vector) -> f32 {
+        let direction = self.end - self.start;
+        let length_squared = direction.dot(direction);
+        let t = if length_squared == 0.0 {
+            0.0
+        } else {
+            ((p - self.start).dot(direction) / length_squared).clamp(0.0, 1.0)
+        };
+        (p - self.point_at(t)).length()
+    }
+
+    // The part inside the axis-aligned rectangle, by Liang-Barsky
+    pub fn clip_to_rect(&self, min: Vector, max: Vector) -> Option<Segment> {
+        let direction = self.end - self.start;
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        // Each edge as p * t <= q: leaving the rectangle where p > 0, entering where p < 0
+        let edges = [
+            (-direction.x, self.start.x - min.x),
+            (direction.x, max.x - self.start.x),
+            (-direction.y, self.start.y - min.y),
+            (direction.y, max.y - self.start.y),
+        ];
+        for (p, q) in edges {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else if p < 0.0 {
+                t0 = t0.max(q / p);
+            } else {
+                t1 = t1.min(q / p);
+            }
+        }
+        if t0 > t1 {
+            None
+        } else {
+            Some(Segment::new(self.point_at(t0), self.point_at(t1)))
+        }
+    }
+
+    // The part inside the frame's parallelogram: clipped to the unit square in the frame's own coordinates
+    pub fn clip_to_frame(&self, frame: &Frame) -> Option<Segment> {
+        Segment::new(frame.unmap(self.start)?, frame.unmap(self.end)?)
+            .clip_to_rect(Vector::zero(), Vector::one())
+            .map(|unit| frame.map_segment(unit))
+    }
 }
 
 #[cfg(test)]
@@ -38,4 +118,60 @@ mod tests {
             Segment::new(v3, v1),
         ]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn measurements() {
+        let segment = Segment::new(Vector::new(1.0, 1.0), Vector::new(4.0, 5.0));
+        assert_eq!(segment.length(), 5.0);
+        assert_eq!(segment.midpoint(), Vector::new(2.5, 3.0));
+        assert_eq!(segment.point_at(0.0), segment.start);
+        assert_eq!(segment.point_at(1.0), segment.end);
+    }
+
+    #[test]
+    fn intersection() {
+        let across = Segment::new(Vector::new(0.0, 1.0), Vector::new(4.0, 1.0));
+        let down = Segment::new(Vector::new(1.0, 0.0), Vector::new(1.0, 4.0));
+        assert_eq!(across.intersection(&down), Some(Vector::new(1.0, 1.0)));
+        assert_eq!(down.intersection(&across), Some(Vector::new(1.0, 1.0)));
+        // Touching at an end
+        let touching = Segment::new(Vector::new(4.0, 1.0), Vector::new(5.0, 3.0));
+        assert_eq!(across.intersection(&touching), Some(Vector::new(4.0, 1.0)));
+        // Missing, and parallel
+        assert_eq!(across.intersection(&Segment::new(Vector::new(5.0, 0.0), Vector::new(5.0, 4.0))), None);
+        assert_eq!(across.intersection(&Segment::new(Vector::new(0.0, 2.0), Vector::new(4.0, 2.0))), None);
+    }
+
+    #[test]
+    fn distance_to() {
+        let segment = Segment::new(Vector::zero(), Vector::new(10.0, 0.0));
+        assert_eq!(segment.distance_to(Vector::new(5.0, 3.0)), 3.0);
+        assert_eq!(segment.distance_to(Vector::new(13.0, 4.0)), 5.0);
+        assert_eq!(segment.distance_to(Vector::new(-3.0, -4.0)), 5.0);
+        assert_eq!(Segment::new(Vector::one(), Vector::one()).distance_to(Vector::new(4.0, 5.0)), 5.0);
+    }
+
+    #[test]
+    fn clip_to_rect() {
+        let (min, max) = (Vector::zero(), Vector::new(10.0, 10.0));
+        let through = Segment::new(Vector::new(-5.0, 5.0), Vector::new(15.0, 5.0));
+        assert_eq!(through.clip_to_rect(min, max), Some(Segment::new(Vector::new(0.0, 5.0), Vector::new(10.0, 5.0))));
+        let inside = Segment::new(Vector::new(1.0, 1.0), Vector::new(2.0, 3.0));
+        assert_eq!(inside.clip_to_rect(min, max), Some(inside));
+        let diagonal = Segment::new(Vector::new(-5.0, -5.0), Vector::new(5.0, 5.0));
+        assert_eq!(diagonal.clip_to_rect(min, max), Some(Segment::new(Vector::zero(), Vector::new(5.0, 5.0))));
+        let outside = Segment::new(Vector::new(-5.0, 5.0), Vector::new(5.0, 20.0));
+        assert_eq!(outside.clip_to_rect(min, max), None);
+        let beside = Segment::new(Vector::new(11.0, 0.0), Vector::new(11.0, 10.0));
+        assert_eq!(beside.clip_to_rect(min, max), None);
+    }
+
+    #[test]
+    fn clip_to_frame() {
+        let frame = Frame::new(Vector::zero(), Vector::new(10.0, 10.0), Vector::new(0.0, 10.0));
+        let across = Segment::new(Vector::new(-5.0, 10.0), Vector::new(20.0, 10.0));
+        assert_eq!(across.clip_to_frame(&frame), Some(Segment::new(Vector::new(0.0, 10.0), Vector::new(10.0, 10.0))));
+        let below = Segment::new(Vector::new(0.0, 25.0), Vector::new(10.0, 25.0));
+        assert_eq!(below.clip_to_frame(&frame), None);
+    }
+}
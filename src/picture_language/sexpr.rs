@@ -0,0 +1,262 @@
+use std::fmt;
+use std::io;
+
+// Lisp's S-expressions: numbers, symbols, strings and lists. Comments run from ; to the end
+// of the line.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Number(f32),
+    Symbol(String),
+    Str(String),
+    List(Vec<Expr>),
+}
+
+// Lines longer than this are broken into one item per line when pretty printing
+const LINE_LENGTH: usize = 100;
+
+impl Expr {
+    pub fn symbol(name: &str) -> Self {
+        Expr::Symbol(name.to_string())
+    }
+
+    pub fn list(items: Vec<Expr>) -> Self {
+        Expr::List(items)
+    }
+
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            Expr::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_symbol(&self) -> Option<&str> {
+        match self {
+            Expr::Symbol(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Expr::Str(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Expr]> {
+        match self {
+            Expr::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    // A list starting with the symbol, such as (frame ...), without its head
+    pub fn tagged(&self, tag: &str) -> Option<&[Expr]> {
+        match self.as_list()? {
+            [head, rest @ ..] if head.as_symbol() == Some(tag) => Some(rest),
+            _ => None,
+        }
+    }
+
+    // Short lists on one line, longer ones with an item on each line, indented under the first
+    pub fn pretty(&self) -> String {
+        let mut text = String::new();
+        self.write_pretty(&mut text, 0);
+        text
+    }
+
+    fn write_pretty(&self, text: &mut String, indent: usize) {
+        let flat = self.to_string();
+        match self {
+            Expr::List(items) if indent + flat.len() > LINE_LENGTH && items.len() > 1 => {
+                text.push('(');
+                items[0].write_pretty(text, indent + 1);
+                for item in &items[1..] {
+                    text.push('\n');
+                    text.push_str(&" ".repeat(indent + 2));
+                    item.write_pretty(text, indent + 2);
+                }
+                text.push(')');
+            }
+            _ => text.push_str(&flat),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(number) => write!(f, "{}", number),
+            Expr::Symbol(name) => write!(f, "{}", name),
+            Expr::Str(text) => write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")),
+            Expr::List(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Every expression in the text, in order
+pub fn parse_all(text: &str) -> io::Result<Vec<Expr>> {
+    let mut reader = Reader { chars: text.chars().collect(), position: 0, line: 1 };
+    let mut exprs = vec![];
+    while reader.skip_space() {
+        exprs.push(reader.expr()?);
+    }
+    Ok(exprs)
+}
+
+// Exactly one expression
+pub fn parse(text: &str) -> io::Result<Expr> {
+    let mut exprs = parse_all(text)?;
+    match exprs.len() {
+        1 => Ok(exprs.remove(0)),
+        0 => Err(invalid("no expression")),
+        _ => Err(invalid("more than one expression")),
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl Reader {
+    // Skips whitespace and comments, returning false at the end of the text
+    fn skip_space(&mut self) -> bool {
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.position += 1;
+                }
+            } else if c.is_whitespace() {
+                self.next();
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expr(&mut self) -> io::Result<Expr> {
+        match self.next() {
+            Some('(') => {
+                let mut items = vec![];
+                loop {
+                    if !self.skip_space() {
+                        return Err(self.error("missing )"));
+                    }
+                    if self.peek() == Some(')') {
+                        self.next();
+                        return Ok(Expr::List(items));
+                    }
+                    items.push(self.expr()?);
+                }
+            }
+            Some(')') => Err(self.error("unexpected )")),
+            Some('"') => self.string(),
+            Some(c) => {
+                let mut token = c.to_string();
+                while let Some(c) = self.peek().filter(|c| !c.is_whitespace() && !"();\"".contains(*c)) {
+                    token.push(c);
+                    self.next();
+                }
+                Ok(token.parse().map(Expr::Number).unwrap_or(Expr::Symbol(token)))
+            }
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn string(&mut self) -> io::Result<Expr> {
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(Expr::Str(text)),
+                Some('\\') => match self.next() {
+                    Some('n') => text.push('\n'),
+                    Some(c @ ('\\' | '"')) => text.push(c),
+                    _ => return Err(self.error("unknown escape in string")),
+                },
+                Some(c) => text.push(c),
+                None => return Err(self.error("missing \"")),
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> io::Error {
+        invalid(&format!("line {}: {}", self.line, message))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_atoms_and_lists() {
+        assert_eq!(parse("(frame (0 -1.5) \"a \\\"b\\\"\" ; comment\n ())").unwrap(), Expr::list(vec![
+            Expr::symbol("frame"),
+            Expr::list(vec![Expr::Number(0.0), Expr::Number(-1.5)]),
+            Expr::Str("a \"b\"".to_string()),
+            Expr::list(vec![]),
+        ]));
+        assert_eq!(parse_all("1 x").unwrap(), vec![Expr::Number(1.0), Expr::symbol("x")]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("(1\n(2)").unwrap_err().to_string(), "line 2: missing )");
+        assert!(parse(")").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("1 2").is_err());
+    }
+
+    #[test]
+    fn print_round_trip() {
+        let expr = Expr::list(vec![
+            Expr::symbol("text"), Expr::Str("say \"hi\"\n".to_string()), Expr::Number(0.1), Expr::Number(100.0),
+        ]);
+        assert_eq!(expr.to_string(), "(text \"say \\\"hi\\\"\\n\" 0.1 100)");
+        assert_eq!(parse(&expr.to_string()).unwrap(), expr);
+    }
+
+    #[test]
+    fn pretty() {
+        let point = Expr::list(vec![Expr::Number(0.25), Expr::Number(0.75)]);
+        let short = Expr::list(vec![Expr::symbol("points"), point.clone()]);
+        assert_eq!(short.pretty(), "(points (0.25 0.75))");
+        let long = Expr::list([vec![Expr::symbol("points")], vec![point; 10]].concat());
+        let pretty = long.pretty();
+        assert_eq!(pretty.lines().count(), 11);
+        assert!(pretty.lines().all(|line| line.len() <= LINE_LENGTH));
+        assert!(pretty.starts_with("(points\n  (0.25 0.75)\n"));
+        assert_eq!(parse(&pretty).unwrap(), long);
+    }
+}
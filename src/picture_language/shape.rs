@@ -1,25 +1,285 @@
+use std::rc::Rc;
+use crate::picture_language::bitmap_font;
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::curve::{self, Curve};
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
 use crate::picture_language::vector::Vector;
-use speedy2d::color::Color;
 use crate::picture_language::segment::{Segment};
+use crate::picture_language::stroke::MITER_LIMIT;
+use crate::picture_language::style::{LineCap, LineJoin, Style};
+use crate::picture_language::transform::Transform;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Shape {
-    Line(Segment, f32, Color),
-    PolyLine(Vec<(Vector, Vector)>, f32, Color),
-    Circle(Vector, f32, Color), // radius
+    Line(Segment, Style),
+    PolyLine(Vec<Vector>, bool, Style), // closed
+    Path(Vec<Curve>, bool, Style), // closed; filled as well as stroked, like a polygon
+    Circle(Vector, f32, Style), // radius
+    // The points centre + axis1 * cos(t) + axis2 * sin(t): a circle mapped through a frame.
+    // The axes need not be perpendicular.
+    Ellipse(Vector, Vector, Vector, Style),
+    Polygon(Vec<Vector>, Style), // closed
+    Rect(Vector, Vector, Style), // min and max corners, axis aligned
+    Text(String, Vector, f32, Anchor, Style), // position and size
+    Image(Rc<Image>, Frame, f32), // the image's unit square, first row along edge1, mapped into the frame, and opacity
 }
 
+// Which point of a text's box is placed at its position
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // How far across and down the box the anchor is, from 0.0 to 1.0
+    pub fn fractions(&self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Centre => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+
+    // Where a box of the given size starts when anchored at position
+    pub fn top_left(&self, position: &Vector, size: &Vector) -> Vector {
+        let (across, down) = self.fractions();
+        *position - Vector::new(size.x * across, size.y * down)
+    }
+}
+
+// Widths and dashes in the style are in device units
 impl Shape {
-    pub fn new_line(segment: Segment, thickness: f32, colour: Color) -> Shape {
-        Shape::Line(segment, thickness, colour)
+    pub fn new_line(segment: Segment, style: Style) -> Shape {
+        Shape::Line(segment, style)
+    }
+
+    pub fn new_poly_line(points: Vec<Vector>, closed: bool, style: Style) -> Shape {
+        Shape::PolyLine(points, closed, style)
+    }
+
+    pub fn new_path(curves: Vec<Curve>, closed: bool, style: Style) -> Shape {
+        Shape::Path(curves, closed, style)
+    }
+
+    pub fn new_circle(centre: &Vector, radius: f32, style: Style) -> Shape {
+        Shape::Circle(*centre, radius, style)
+    }
+
+    pub fn new_ellipse(centre: &Vector, axis1: Vector, axis2: Vector, style: Style) -> Shape {
+        Shape::Ellipse(*centre, axis1, axis2, style)
+    }
+
+    pub fn new_polygon(points: Vec<Vector>, style: Style) -> Shape {
+        Shape::Polygon(points, style)
+    }
+
+    pub fn new_rect(min: Vector, max: Vector, style: Style) -> Shape {
+        Shape::Rect(min, max, style)
+    }
+
+    pub fn new_image(image: &Rc<Image>, frame: Frame) -> Shape {
+        Shape::Image(image.clone(), frame, 1.0)
+    }
+
+    pub fn new_text(text: &str, position: Vector, size: f32, anchor: Anchor, style: Style) -> Shape {
+        Shape::Text(text.to_string(), position, size, anchor, style)
+    }
+
+    // Everything the shape may draw on, including its stroke. Text is measured in the bitmap
+    // font, so a window font may be a little wider or narrower.
+    pub fn bounds(&self) -> Option<Bounds> {
+        match self {
+            Shape::Line(segment, style) =>
+                Some(Bounds::from_points(&[segment.start, segment.end])?.expand(stroke_margin(style, false))),
+            Shape::PolyLine(points, _, style) | Shape::Polygon(points, style) =>
+                Some(Bounds::from_points(points)?.expand(stroke_margin(style, points.len() > 2))),
+            Shape::Path(curves, _, style) => {
+                let points = curve::flatten_path(curves, curve::TOLERANCE);
+                Some(Bounds::from_points(&points)?.expand(curve::TOLERANCE + stroke_margin(style, true)))
+            }
+            Shape::Circle(centre, radius, style) =>
+                Some(Bounds::new(*centre, *centre).expand(radius.abs() + stroke_margin(style, false))),
+            Shape::Ellipse(centre, axis1, axis2, style) => {
+                let extent = Vector::new(axis1.x.hypot(axis2.x), axis1.y.hypot(axis2.y));
+                Some(Bounds::new(*centre - extent, *centre + extent).expand(stroke_margin(style, false)))
+            }
+            Shape::Rect(min, max, style) =>
+                Some(Bounds::from_points(&[*min, *max])?.expand(stroke_margin(style, true))),
+            Shape::Text(text, position, size, anchor, _) => {
+                let size = bitmap_font::text_size(text, *size);
+                let top_left = anchor.top_left(position, &size);
+                Some(Bounds::new(top_left, top_left + size))
+            }
+            Shape::Image(_, frame, _) => Some(frame.bounds()),
+        }
+    }
+
+    // Moved by a transform of device space. Widths, dashes and text sizes scale by the square
+    // root of the change in area, as with a frame's scale. Circles stay circles, and rectangles
+    // rectangles, only while the transform keeps them so.
+    pub fn transformed(&self, transform: &Transform) -> Shape {
+        let scale = transform.determinant().abs().sqrt();
+        let points = |points: &[Vector]| points.iter().map(|p| transform.apply(*p)).collect();
+        match self {
+            Shape::Line(segment, style) =>
+                Shape::new_line(Segment::new(transform.apply(segment.start), transform.apply(segment.end)),
+                                style.scaled(scale)),
+            Shape::PolyLine(vertices, closed, style) =>
+                Shape::new_poly_line(points(vertices), *closed, style.scaled(scale)),
+            Shape::Path(curves, closed, style) => {
+                let frame = Frame::from(*transform);
+                Shape::new_path(curves.iter().map(|curve| curve.map(&frame)).collect(), *closed, style.scaled(scale))
+            }
+            Shape::Circle(centre, radius, style) => {
+                let (axis1, axis2) = (transform.apply_vector(Vector::new(*radius, 0.0)),
+                                      transform.apply_vector(Vector::new(0.0, *radius)));
+                if axis1.length() == axis2.length() && axis1.x * axis2.x + axis1.y * axis2.y == 0.0 {
+                    Shape::new_circle(&transform.apply(*centre), axis1.length(), style.scaled(scale))
+                } else {
+                    Shape::new_ellipse(&transform.apply(*centre), axis1, axis2, style.scaled(scale))
+                }
+            }
+            Shape::Ellipse(centre, axis1, axis2, style) =>
+                Shape::new_ellipse(&transform.apply(*centre), transform.apply_vector(*axis1),
+                                   transform.apply_vector(*axis2), style.scaled(scale)),
+            Shape::Polygon(vertices, style) =>
+                Shape::new_polygon(points(vertices), style.scaled(scale)),
+            Shape::Rect(min, max, style) => {
+                let corners = [*min, Vector::new(max.x, min.y), *max, Vector::new(min.x, max.y)];
+                if transform.x_axis.y == 0.0 && transform.y_axis.x == 0.0 {
+                    let bounds = Bounds::from_points(&points(&corners)).unwrap();
+                    Shape::new_rect(bounds.min, bounds.max, style.scaled(scale))
+                } else {
+                    Shape::new_polygon(points(&corners), style.scaled(scale))
+                }
+            }
+            Shape::Text(text, position, size, anchor, style) =>
+                Shape::new_text(text, transform.apply(*position), size * scale, *anchor, style.scaled(scale)),
+            Shape::Image(image, frame, opacity) =>
+                Shape::Image(image.clone(), frame.transformed(transform), *opacity),
+        }
+    }
+}
+
+// How far a stroke reaches beyond the outline: half its width, or further at mitered corners
+// and square caps
+fn stroke_margin(style: &Style, corners: bool) -> f32 {
+    if style.stroke.is_none() {
+        return 0.0;
+    }
+    let half = style.width / 2.0;
+    if corners && style.join == LineJoin::Miter {
+        half * MITER_LIMIT
+    } else if style.cap == LineCap::Square {
+        half * std::f32::consts::SQRT_2
+    } else {
+        half
+    }
+}
+
+// Points around an ellipse, close enough together that the polygon through them is
+// within a quarter of a unit of the curve
+pub fn ellipse_points(centre: &Vector, axis1: Vector, axis2: Vector) -> Vec<Vector> {
+    let radius = axis1.length().max(axis2.length());
+    let step = if radius > 0.25 { 2.0 * (1.0 - 0.25 / radius).acos() } else { std::f32::consts::PI };
+    let count = ((2.0 * std::f32::consts::PI / step).ceil() as usize).clamp(8, 1024);
+    (0..count)
+        .map(|i| {
+            let t = 2.0 * std::f32::consts::PI * i as f32 / count as f32;
+            *centre + axis1.scale(t.cos()) + axis2.scale(t.sin())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+
+    #[test]
+    fn ellipse_points() {
+        let centre = Vector::new(10.0, 20.0);
+        let points = super::ellipse_points(&centre, Vector::new(100.0, 0.0), Vector::new(30.0, 50.0));
+        assert!(points.len() > 8);
+        assert_eq!(points[0], Vector::new(110.0, 20.0));
+        points.iter().for_each(|p| {
+            // Solve p - centre = axis1 * u + axis2 * v; every point is on the ellipse u^2 + v^2 = 1
+            let v = (p.y - centre.y) / 50.0;
+            let u = (p.x - centre.x - 30.0 * v) / 100.0;
+            assert!((u * u + v * v - 1.0).abs() < 1e-4);
+        });
+    }
+
+    #[test]
+    fn bounds_include_the_stroke() {
+        let line = Shape::new_line(Segment::new(Vector::new(0.0, 0.0), Vector::new(10.0, 0.0)),
+                                   Style::stroke(2.0, Colour::BLACK));
+        assert_eq!(line.bounds(), Some(Bounds::new(Vector::new(-1.0, -1.0), Vector::new(11.0, 1.0))));
+        let circle = Shape::new_circle(&Vector::new(5.0, 5.0), 2.0, Style::fill(Colour::RED));
+        assert_eq!(circle.bounds(), Some(Bounds::new(Vector::new(3.0, 3.0), Vector::new(7.0, 7.0))));
+        let ellipse = Shape::new_ellipse(&Vector::zero(), Vector::new(3.0, 0.0), Vector::new(0.0, 4.0),
+                                         Style::fill_and_stroke(Colour::RED, 2.0, Colour::BLACK));
+        assert_eq!(ellipse.bounds(), Some(Bounds::new(Vector::new(-4.0, -5.0), Vector::new(4.0, 5.0))));
+        let square = Shape::new_rect(Vector::zero(), Vector::one(), Style::stroke(2.0, Colour::BLACK)
+            .with_join(LineJoin::Round));
+        assert_eq!(square.bounds(), Some(Bounds::new(Vector::new(-1.0, -1.0), Vector::new(2.0, 2.0))));
+        assert_eq!(Shape::new_polygon(vec![], Style::fill(Colour::RED)).bounds(), None);
+    }
+
+    #[test]
+    fn bounds_of_curves_and_text() {
+        let arc = Shape::new_path(vec![Curve::circular_arc(Vector::zero(), 10.0, 0.0, std::f32::consts::PI)],
+                                  false, Style::fill(Colour::RED));
+        let bounds = arc.bounds().unwrap();
+        assert!((bounds.min.y - -curve::TOLERANCE).abs() < 1e-3 && (bounds.max.y - 10.0).abs() < 0.5, "{:?}", bounds);
+        let text = Shape::new_text("ab", Vector::new(100.0, 100.0), 8.0, Anchor::BottomRight, Style::fill(Colour::RED));
+        assert_eq!(text.bounds(), Some(Bounds::new(Vector::new(88.0, 92.0), Vector::new(100.0, 100.0))));
+    }
+
+    #[test]
+    fn transformed() {
+        let double = Transform::scale(2.0, 2.0).then(&Transform::translate(Vector::new(10.0, 0.0)));
+        let circle = Shape::new_circle(&Vector::one(), 3.0, Style::stroke(1.0, Colour::RED));
+        assert_eq!(circle.transformed(&double), Shape::new_circle(&Vector::new(12.0, 2.0), 6.0,
+                                                                  Style::stroke(2.0, Colour::RED)));
+        let stretch = Transform::scale(2.0, 8.0);
+        assert_eq!(circle.transformed(&stretch), Shape::new_ellipse(&Vector::new(2.0, 8.0), Vector::new(6.0, 0.0),
+                                                                    Vector::new(0.0, 24.0),
+                                                                    Style::stroke(4.0, Colour::RED)));
+        let rect = Shape::new_rect(Vector::zero(), Vector::one(), Style::fill(Colour::RED));
+        assert_eq!(rect.transformed(&Transform::scale(-1.0, 1.0)),
+                   Shape::new_rect(Vector::new(-1.0, 0.0), Vector::new(0.0, 1.0), Style::fill(Colour::RED)));
+        assert!(matches!(rect.transformed(&Transform::shear(1.0, 0.0)), Shape::Polygon(..)));
+        let text = Shape::new_text("a", Vector::one(), 10.0, Anchor::Centre, Style::fill(Colour::RED));
+        assert_eq!(text.transformed(&double), Shape::new_text("a", Vector::new(12.0, 2.0), 20.0, Anchor::Centre,
+                                                              Style::fill(Colour::RED)));
     }
 
-    pub fn new_poly_line(s: Vec<Segment>, thickness: f32, colour: Color) -> Shape {
-        Shape::PolyLine(s.iter().map(|s| (s.start, s.end)).collect(),
-                        thickness, colour)
+    #[test]
+    fn anchor() {
+        let size = Vector::new(40.0, 10.0);
+        assert_eq!(Anchor::TopLeft.top_left(&Vector::new(100.0, 50.0), &size), Vector::new(100.0, 50.0));
+        assert_eq!(Anchor::Centre.top_left(&Vector::new(100.0, 50.0), &size), Vector::new(80.0, 45.0));
+        assert_eq!(Anchor::BottomRight.top_left(&Vector::new(100.0, 50.0), &size), Vector::new(60.0, 40.0));
     }
 
-    pub fn new_circle(centre: &Vector, radius: f32, colour: Color) -> Shape {
-        Shape::Circle(*centre, radius, colour)
+    #[test]
+    fn small_ellipses_still_have_points() {
+        assert_eq!(super::ellipse_points(&Vector::zero(), Vector::zero(), Vector::zero()).len(), 8);
     }
 }
\ No newline at end of file
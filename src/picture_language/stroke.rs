@@ -0,0 +1,338 @@
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::shape::ellipse_points;
+use crate::picture_language::style::{LineCap, LineJoin, Style};
+use crate::picture_language::vector::Vector;
+
+// Miter joins longer than this many times the thickness are bevelled instead, as in SVG
+pub const MITER_LIMIT: f32 = 4.0;
+
+// The area covered by a thick polyline, dashed if the style says so, as convex polygons:
+// one for each segment, join and cap. The pieces overlap, so a translucent stroke needs
+// them filled as one region.
+pub fn stroke(points: &[Vector], closed: bool, style: &Style) -> Vec<Vec<Vector>> {
+    match style.dash_pattern() {
+        Some(pattern) => dash(points, closed, &pattern).iter()
+            .flat_map(|dash| stroke_pieces(dash, false, style))
+            .collect(),
+        None => stroke_pieces(points, closed, style),
+    }
+}
+
+// The same region as the convex pieces, cut where they overlap so that nothing is covered
+// twice, for backends that blend each polygon they fill. The pieces come out convex too.
+pub fn disjoint(pieces: &[Vec<Vector>]) -> Vec<Vec<Vector>> {
+    let bounds: Vec<Option<Bounds>> = pieces.iter().map(|piece| Bounds::from_points(piece)).collect();
+    let mut region = vec![];
+    for (i, piece) in pieces.iter().enumerate() {
+        let mut fragments = vec![piece.clone()];
+        for (earlier, earlier_bounds) in pieces[..i].iter().zip(&bounds) {
+            if let (Some(b1), Some(b2)) = (&bounds[i], earlier_bounds) {
+                if b1.intersects(b2) {
+                    fragments = fragments.iter().flat_map(|fragment| subtract(fragment, earlier)).collect();
+                }
+            }
+        }
+        region.extend(fragments);
+    }
+    region
+}
+
+// The parts of the convex polygon outside the convex hole: what lies beyond each of the hole's
+// edges in turn, of what is left inside the edges before it
+fn subtract(polygon: &[Vector], hole: &[Vector]) -> Vec<Vec<Vector>> {
+    let orientation = area(hole).signum();
+    if orientation == 0.0 {
+        return vec![polygon.to_vec()];
+    }
+    let edges: Vec<(Vector, Vector)> = (0..hole.len()).map(|i| (hole[i], hole[(i + 1) % hole.len()])).collect();
+    let beyond = |p: Vector, (a, b): (Vector, Vector)| (b - a).cross(p - a) * orientation <= 0.0;
+    if edges.iter().any(|edge| polygon.iter().all(|p| beyond(*p, *edge))) {
+        return vec![polygon.to_vec()];
+    }
+    let mut outside = vec![];
+    let mut rest = polygon.to_vec();
+    for (a, b) in edges {
+        let part = half_plane(&rest, a, b, -orientation);
+        if area(&part).abs() > SLIVER {
+            outside.push(part);
+        }
+        rest = half_plane(&rest, a, b, orientation);
+        if area(&rest).abs() <= SLIVER {
+            break;
+        }
+    }
+    outside
+}
+
+// Fragments with less area than this are rounding errors
+const SLIVER: f32 = 1e-6;
+
+// Sutherland-Hodgman against a single edge, keeping the side that turns the edge's way
+fn half_plane(polygon: &[Vector], a: Vector, b: Vector, side: f32) -> Vec<Vector> {
+    let distance = |p: Vector| (b - a).cross(p - a) * side;
+    let mut kept = vec![];
+    for (i, p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        let (dp, dq) = (distance(*p), distance(q));
+        if dp >= 0.0 {
+            kept.push(*p);
+        }
+        if (dp > 0.0 && dq < 0.0) || (dp < 0.0 && dq > 0.0) {
+            kept.push(*p + (q - *p).scale(dp / (dp - dq)));
+        }
+    }
+    kept
+}
+
+// Positive when the points turn anticlockwise in y-up coordinates
+fn area(polygon: &[Vector]) -> f32 {
+    (0..polygon.len()).map(|i| polygon[i].cross(polygon[(i + 1) % polygon.len()])).sum::<f32>() / 2.0
+}
+
+// The stretches of the path that the pattern's on lengths cover, each as an open path
+pub fn dash(points: &[Vector], closed: bool, pattern: &[f32]) -> Vec<Vec<Vector>> {
+    let mut path = points.to_vec();
+    if closed && !path.is_empty() {
+        path.push(path[0]);
+    }
+    let mut dashes = vec![];
+    let mut current: Vec<Vector> = path.first().into_iter().copied().collect();
+    let (mut index, mut remaining, mut on) = (0, pattern[0], true);
+    for (from, to) in path.iter().zip(path.iter().skip(1)) {
+        let length = (*to - *from).length();
+        let mut travelled = 0.0;
+        while length - travelled > remaining {
+            travelled += remaining;
+            let p = *from + (*to - *from).scale(travelled / length);
+            if on {
+                current.push(p);
+                dashes.push(std::mem::take(&mut current));
+            } else {
+                current = vec![p];
+            }
+            on = !on;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= length - travelled;
+        if on {
+            current.push(*to);
+        }
+    }
+    if on && current.len() > 1 {
+        dashes.push(current);
+    }
+    dashes
+}
+
+fn stroke_pieces(points: &[Vector], closed: bool, style: &Style) -> Vec<Vec<Vector>> {
+    let half = style.width / 2.0;
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 2 && points.first() == points.last() {
+        points.pop();
+    }
+    if half <= 0.0 {
+        return vec![];
+    }
+    if points.len() < 2 {
+        // A lone point only shows with caps that reach past it
+        return match (points.first(), style.cap, closed) {
+            (Some(p), LineCap::Round, false) => vec![disc(p, half)],
+            (Some(p), LineCap::Square, false) => vec![vec![
+                *p + Vector::new(-half, -half), *p + Vector::new(half, -half),
+                *p + Vector::new(half, half), *p + Vector::new(-half, half),
+            ]],
+            _ => vec![],
+        };
+    }
+    let count = if closed { points.len() } else { points.len() - 1 };
+    let segments: Vec<(Vector, Vector)> = (0..count)
+        .map(|i| (points[i], points[(i + 1) % points.len()]))
+        .collect();
+    let mut pieces: Vec<Vec<Vector>> = segments.iter()
+        .map(|(from, to)| {
+            let n = normal(*to - *from).scale(half);
+            vec![*from + n, *to + n, *to - n, *from - n]
+        })
+        .collect();
+    let joins = if closed { count } else { count - 1 };
+    pieces.extend((0..joins)
+        .filter_map(|i| join(segments[i].0, segments[i].1, segments[(i + 1) % count].1, half, style.join)));
+    if !closed {
+        let last = points.len() - 1;
+        pieces.extend(cap(points[1], points[0], half, style.cap));
+        pieces.extend(cap(points[last - 1], points[last], half, style.cap));
+    }
+    pieces
+}
+
+// Fills the gap on the outside of the turn at `at`
+fn join(from: Vector, at: Vector, to: Vector, half: f32, join: LineJoin) -> Option<Vec<Vector>> {
+    if join == LineJoin::Round {
+        return Some(disc(&at, half));
+    }
+    let (n_in, n_out) = (normal(at - from), normal(to - at));
+    let turn = (at - from).cross(to - at);
+    if turn == 0.0 {
+        return None;
+    }
+    let side = if turn > 0.0 { -half } else { half };
+    let (outer_in, outer_out) = (n_in.scale(side), n_out.scale(side));
+    let cos = n_in.dot(n_out);
+    if join == LineJoin::Miter && (2.0 / (1.0 + cos)).sqrt() <= MITER_LIMIT {
+        let tip = at + (outer_in + outer_out).scale(1.0 / (1.0 + cos));
+        Some(vec![at, at + outer_in, tip, at + outer_out])
+    } else {
+        Some(vec![at, at + outer_in, at + outer_out])
+    }
+}
+
+// Extends the line past `end`, having come from `previous`
+fn cap(previous: Vector, end: Vector, half: f32, cap: LineCap) -> Option<Vec<Vector>> {
+    match cap {
+        LineCap::Butt => None,
+        LineCap::Round => Some(disc(&end, half)),
+        LineCap::Square => {
+            let n = normal(end - previous).scale(half);
+            let along = Vector::new(n.y, -n.x);
+            Some(vec![end + n, end + n + along, end - n + along, end - n])
+        }
+    }
+}
+
+fn disc(centre: &Vector, radius: f32) -> Vec<Vector> {
+    ellipse_points(centre, Vector::new(radius, 0.0), Vector::new(0.0, radius))
+}
+
+// Of unit length, a quarter turn from the direction
+fn normal(direction: Vector) -> Vector {
+    Vector::new(-direction.y, direction.x).scale(1.0 / direction.length())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picture_language::colour::Colour;
+
+    fn style(join: LineJoin, cap: LineCap) -> Style {
+        Style::stroke(2.0, Colour::BLACK).with_join(join).with_cap(cap)
+    }
+
+    fn corner() -> Vec<Vector> {
+        vec![Vector::zero(), Vector::new(10.0, 0.0), Vector::new(10.0, 10.0)]
+    }
+
+    #[test]
+    fn segment() {
+        let pieces = stroke_pieces(&[Vector::zero(), Vector::new(10.0, 0.0)], false,
+                                   &style(LineJoin::Miter, LineCap::Butt));
+        assert_eq!(pieces, vec![vec![
+            Vector::new(0.0, 1.0), Vector::new(10.0, 1.0), Vector::new(10.0, -1.0), Vector::new(0.0, -1.0),
+        ]]);
+    }
+
+    #[test]
+    fn miter_join() {
+        let pieces = stroke_pieces(&corner(), false, &style(LineJoin::Miter, LineCap::Butt));
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces[2], vec![
+            Vector::new(10.0, 0.0), Vector::new(10.0, -1.0), Vector::new(11.0, -1.0), Vector::new(11.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn bevel_join() {
+        let pieces = stroke_pieces(&corner(), false, &style(LineJoin::Bevel, LineCap::Butt));
+        assert_eq!(pieces[2], vec![Vector::new(10.0, 0.0), Vector::new(10.0, -1.0), Vector::new(11.0, 0.0)]);
+    }
+
+    #[test]
+    fn sharp_miters_are_bevelled() {
+        let hairpin = [Vector::zero(), Vector::new(10.0, 0.0), Vector::new(0.0, 1.0)];
+        let pieces = stroke_pieces(&hairpin, false, &style(LineJoin::Miter, LineCap::Butt));
+        assert_eq!(pieces[2].len(), 3);
+    }
+
+    #[test]
+    fn round_join_and_caps() {
+        let pieces = stroke_pieces(&corner(), false, &style(LineJoin::Round, LineCap::Round));
+        assert_eq!(pieces.len(), 5);
+        assert_eq!(pieces[3][0], Vector::new(1.0, 0.0));
+        assert_eq!(pieces[4][0], Vector::new(11.0, 10.0));
+    }
+
+    #[test]
+    fn square_caps() {
+        let pieces = stroke_pieces(&[Vector::zero(), Vector::new(10.0, 0.0)], false,
+                                   &style(LineJoin::Miter, LineCap::Square));
+        assert_eq!(pieces[1], vec![
+            Vector::new(0.0, -1.0), Vector::new(-1.0, -1.0), Vector::new(-1.0, 1.0), Vector::new(0.0, 1.0),
+        ]);
+        assert_eq!(pieces[2], vec![
+            Vector::new(10.0, 1.0), Vector::new(11.0, 1.0), Vector::new(11.0, -1.0), Vector::new(10.0, -1.0),
+        ]);
+    }
+
+    #[test]
+    fn closed_paths_join_at_the_start_and_have_no_caps() {
+        let square = [Vector::zero(), Vector::new(10.0, 0.0), Vector::new(10.0, 10.0), Vector::new(0.0, 10.0)];
+        let pieces = stroke_pieces(&square, true, &style(LineJoin::Bevel, LineCap::Round));
+        assert_eq!(pieces.len(), 8);
+        let repeated = [&square[..], &[Vector::zero()]].concat();
+        assert_eq!(stroke_pieces(&repeated, true, &style(LineJoin::Bevel, LineCap::Round)), pieces);
+    }
+
+    #[test]
+    fn dash() {
+        let line = [Vector::zero(), Vector::new(10.0, 0.0)];
+        assert_eq!(super::dash(&line, false, &[3.0, 2.0]), vec![
+            vec![Vector::zero(), Vector::new(3.0, 0.0)],
+            vec![Vector::new(5.0, 0.0), Vector::new(8.0, 0.0)],
+        ]);
+    }
+
+    #[test]
+    fn dashes_turn_corners() {
+        assert_eq!(super::dash(&corner(), false, &[12.0, 2.0]), vec![
+            vec![Vector::zero(), Vector::new(10.0, 0.0), Vector::new(10.0, 2.0)],
+            vec![Vector::new(10.0, 4.0), Vector::new(10.0, 10.0)],
+        ]);
+    }
+
+    #[test]
+    fn closed_paths_dash_back_to_the_start() {
+        let square = [Vector::zero(), Vector::new(10.0, 0.0), Vector::new(10.0, 10.0), Vector::new(0.0, 10.0)];
+        let dashes = super::dash(&square, true, &[35.0, 10.0]);
+        assert_eq!(dashes.len(), 1);
+        assert_eq!(dashes[0].last(), Some(&Vector::new(0.0, 5.0)));
+    }
+
+    #[test]
+    fn dashed_stroke() {
+        let line = [Vector::zero(), Vector::new(10.0, 0.0)];
+        let dashed = style(LineJoin::Miter, LineCap::Butt).with_dash(vec![3.0, 2.0]);
+        assert_eq!(stroke(&line, false, &dashed).len(), 2);
+    }
+
+    #[test]
+    fn degenerate_paths() {
+        let point = [Vector::one(), Vector::one()];
+        assert!(stroke_pieces(&point, false, &style(LineJoin::Miter, LineCap::Butt)).is_empty());
+        assert_eq!(stroke_pieces(&point, false, &style(LineJoin::Miter, LineCap::Round)).len(), 1);
+        assert!(stroke_pieces(&[], false, &style(LineJoin::Miter, LineCap::Round)).is_empty());
+    }
+
+    #[test]
+    fn disjoint() {
+        let square = |x: f32, y: f32| vec![Vector::new(x, y), Vector::new(x + 2.0, y), Vector::new(x + 2.0, y + 2.0),
+                                           Vector::new(x, y + 2.0)];
+        let (first, second, apart) = (square(0.0, 0.0), square(1.0, 1.0), square(5.0, 0.0));
+        let region = super::disjoint(&[first.clone(), second.clone(), apart.clone()]);
+        // The first and the one apart are untouched, and the second loses the corner they share
+        assert_eq!(region[0], first);
+        assert_eq!(region.last(), Some(&apart));
+        let areas: f32 = region.iter().map(|piece| area(piece)).sum();
+        assert_eq!(areas, 4.0 + 3.0 + 4.0);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::picture_language::colour::Colour;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+// What the stroke width and dash lengths are measured in. Unit-square lengths grow and
+// shrink with the painter's frame; device lengths are in pixels whatever the frame.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Units {
+    Device,
+    UnitSquare,
+}
+
+// How a shape is drawn. Lines and paths only use the stroke; closed shapes are filled and
+// then outlined. The defaults are SVG's: mitered joins, butt caps and no dashes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Style {
+    pub stroke: Option<Colour>,
+    pub fill: Option<Colour>,
+    pub width: f32,
+    pub units: Units,
+    pub dash: Vec<f32>, // alternating on and off lengths, starting with on
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub opacity: f32,
+}
+
+impl Style {
+    pub fn stroke(width: f32, colour: Colour) -> Self {
+        Style {
+            stroke: Some(colour),
+            fill: None,
+            width,
+            units: Units::Device,
+            dash: vec![],
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            opacity: 1.0,
+        }
+    }
+
+    pub fn fill(colour: Colour) -> Self {
+        Style { stroke: None, fill: Some(colour), ..Style::stroke(0.0, colour) }
+    }
+
+    pub fn fill_and_stroke(fill: Colour, width: f32, stroke: Colour) -> Self {
+        Style { fill: Some(fill), ..Style::stroke(width, stroke) }
+    }
+
+    pub fn with_units(self, units: Units) -> Self {
+        Style { units, ..self }
+    }
+
+    pub fn with_dash(self, dash: Vec<f32>) -> Self {
+        Style { dash, ..self }
+    }
+
+    pub fn with_join(self, join: LineJoin) -> Self {
+        Style { join, ..self }
+    }
+
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        Style { cap, ..self }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Style { opacity, ..self }
+    }
+
+    // The colours to draw with, once the opacity is applied
+    pub fn stroke_colour(&self) -> Option<Colour> {
+        self.stroke.map(|c| self.faded(c))
+    }
+
+    pub fn fill_colour(&self) -> Option<Colour> {
+        self.fill.map(|c| self.faded(c))
+    }
+
+    // Text is drawn in the fill colour, or in the stroke colour when there is no fill
+    pub fn text_colour(&self) -> Option<Colour> {
+        self.fill_colour().or_else(|| self.stroke_colour())
+    }
+
+    fn faded(&self, colour: Colour) -> Colour {
+        Colour { a: colour.a * self.opacity.clamp(0.0, 1.0), ..colour }
+    }
+
+    // Dashes only apply when there is something to draw; an odd number of lengths repeats, as in SVG
+    pub fn dash_pattern(&self) -> Option<Vec<f32>> {
+        let total: f32 = self.dash.iter().sum();
+        if total <= 0.0 || self.dash.iter().any(|length| *length < 0.0) {
+            None
+        } else if self.dash.len() % 2 == 1 {
+            Some([&self.dash[..], &self.dash[..]].concat())
+        } else {
+            Some(self.dash.clone())
+        }
+    }
+
+    // In device units, for a frame that scales lengths by the given factor
+    pub fn to_device(&self, scale: f32) -> Self {
+        Style {
+            width: self.to_device_length(self.width, scale),
+            dash: self.dash.iter().map(|length| self.to_device_length(*length, scale)).collect(),
+            units: Units::Device,
+            ..self.clone()
+        }
+    }
+
+    // With the width and dashes scaled, for a shape that has been scaled by the factor
+    pub fn scaled(&self, factor: f32) -> Self {
+        Style {
+            width: self.width * factor,
+            dash: self.dash.iter().map(|length| length * factor).collect(),
+            ..self.clone()
+        }
+    }
+
+    // Any other length measured in the style's units, such as a font size
+    pub fn to_device_length(&self, length: f32, scale: f32) -> f32 {
+        match self.units {
+            Units::Device => length,
+            Units::UnitSquare => length * scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opacity_fades_both_colours() {
+        let style = Style::fill_and_stroke(Colour::RED, 1.0, Colour::from_rgba(0.0, 0.0, 0.0, 0.5))
+            .with_opacity(0.5);
+        assert_eq!(style.fill_colour(), Some(Colour::from_rgba(1.0, 0.0, 0.0, 0.5)));
+        assert_eq!(style.stroke_colour(), Some(Colour::from_rgba(0.0, 0.0, 0.0, 0.25)));
+        assert_eq!(Style::fill(Colour::RED).stroke_colour(), None);
+        assert_eq!(Style::stroke(1.0, Colour::RED).with_opacity(0.5).text_colour(),
+                   Some(Colour::from_rgba(1.0, 0.0, 0.0, 0.5)));
+    }
+
+    #[test]
+    fn dash_pattern() {
+        let style = Style::stroke(1.0, Colour::BLACK);
+        assert_eq!(style.dash_pattern(), None);
+        assert_eq!(style.clone().with_dash(vec![4.0, 2.0]).dash_pattern(), Some(vec![4.0, 2.0]));
+        assert_eq!(style.clone().with_dash(vec![4.0, 2.0, 1.0]).dash_pattern(),
+                   Some(vec![4.0, 2.0, 1.0, 4.0, 2.0, 1.0]));
+        assert_eq!(style.clone().with_dash(vec![0.0, 0.0]).dash_pattern(), None);
+        assert_eq!(style.with_dash(vec![4.0, -2.0]).dash_pattern(), None);
+    }
+
+    #[test]
+    fn to_device() {
+        let device = Style::stroke(2.0, Colour::BLACK).with_dash(vec![1.0, 3.0]);
+        assert_eq!(device.to_device(100.0), device);
+        let unit = Style::stroke(0.02, Colour::BLACK).with_dash(vec![0.5, 0.25]).with_units(Units::UnitSquare);
+        assert_eq!(unit.to_device(100.0), Style::stroke(2.0, Colour::BLACK).with_dash(vec![50.0, 25.0]));
+        assert_eq!(device.scaled(2.0), Style::stroke(4.0, Colour::BLACK).with_dash(vec![2.0, 6.0]));
+    }
+}
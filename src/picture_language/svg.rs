@@ -0,0 +1,380 @@
+use std::fmt::Write;
+use std::fs;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::coordinates::CoordinateSystem;
+use crate::picture_language::curve::Curve;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::image::Image;
+use crate::picture_language::renderer::{self, Renderer};
+use crate::picture_language::segment::Segment;
+use crate::picture_language::shape::{Anchor, Shape};
+use crate::picture_language::style::{LineCap, LineJoin, Style};
+use crate::picture_language::vector::Vector;
+
+pub fn write_svg(path: &str, shapes: &[Shape], width: u32, height: u32, coordinates: CoordinateSystem)
+                 -> std::io::Result<()> {
+    fs::write(path, to_svg(shapes, width, height, coordinates))
+}
+
+// Matches the window: a white background with shapes drawn in order on top
+pub fn to_svg(shapes: &[Shape], width: u32, height: u32, coordinates: CoordinateSystem) -> String {
+    let mut svg = SvgRenderer::new(width, height);
+    svg.clear(Colour::WHITE);
+    renderer::render_shapes(&coordinates.shapes_to_device(shapes, height as f32), &mut svg);
+    svg.document()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgRenderer {
+    width: u32,
+    height: u32,
+    elements: String,
+}
+
+impl SvgRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        SvgRenderer { width, height, elements: String::new() }
+    }
+
+    pub fn document(&self) -> String {
+        let (width, height) = (self.width, self.height);
+        format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#)
+            + "\n" + &self.elements + "</svg>\n"
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn clear(&mut self, colour: Colour) {
+        self.elements = String::new();
+        writeln!(self.elements, r#"<rect width="{}" height="{}" {}/>"#,
+                 self.width, self.height, paint("fill", &colour)).unwrap();
+    }
+
+    fn draw_polygon(&mut self, points: &[Vector], style: &Style) {
+        let points: Vec<String> = points.iter().map(point).collect();
+        writeln!(self.elements, r#"<polygon points="{}" {}/>"#,
+                 points.join(" "), fill_and_stroke(style)).unwrap();
+    }
+
+    fn draw_line(&mut self, segment: &Segment, style: &Style) {
+        writeln!(self.elements, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+                 segment.start.x, segment.start.y, segment.end.x, segment.end.y,
+                 stroke(style)).unwrap();
+    }
+
+    fn draw_polyline(&mut self, points: &[Vector], closed: bool, style: &Style) {
+        let element = if closed { "polygon" } else { "polyline" };
+        let points: Vec<String> = points.iter().map(point).collect();
+        writeln!(self.elements, r#"<{} points="{}" fill="none" {}/>"#,
+                 element, points.join(" "), stroke(style)).unwrap();
+    }
+
+    // Native path commands, so curves stay smooth however far the SVG is zoomed
+    fn draw_path(&mut self, curves: &[Curve], closed: bool, style: &Style) {
+        let mut data = String::new();
+        let mut last: Option<Vector> = None;
+        for curve in curves {
+            let start = curve.start();
+            match last {
+                None => write!(data, "M{}", point(&start)).unwrap(),
+                Some(end) if end != start => write!(data, " L{}", point(&start)).unwrap(),
+                Some(_) => {}
+            }
+            match curve {
+                Curve::Line(_, end) => write!(data, " L{}", point(end)).unwrap(),
+                Curve::Quadratic(_, control, end) => write!(data, " Q{} {}", point(control), point(end)).unwrap(),
+                Curve::Cubic(_, control1, control2, end) =>
+                    write!(data, " C{} {} {}", point(control1), point(control2), point(end)).unwrap(),
+                Curve::Arc(centre, axis1, axis2, start, end) =>
+                    data += &arc(centre, axis1, axis2, *start, *end),
+            }
+            last = Some(curve.end());
+        }
+        if closed {
+            data += " Z";
+        }
+        writeln!(self.elements, r#"<path d="{}" {}/>"#, data, fill_and_stroke(style)).unwrap();
+    }
+
+    fn draw_rect(&mut self, min: &Vector, max: &Vector, style: &Style) {
+        writeln!(self.elements, r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                 min.x, min.y, max.x - min.x, max.y - min.y, fill_and_stroke(style)).unwrap();
+    }
+
+    fn draw_circle(&mut self, centre: &Vector, radius: f32, style: &Style) {
+        writeln!(self.elements, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                 centre.x, centre.y, radius, fill_and_stroke(style)).unwrap();
+    }
+
+    // The unit circle, mapped onto the ellipse by its axes. The outline keeps its width
+    // rather than being scaled with the circle.
+    fn draw_ellipse(&mut self, centre: &Vector, axis1: &Vector, axis2: &Vector, style: &Style) {
+        let non_scaling = if style.stroke.is_some() { r#" vector-effect="non-scaling-stroke""# } else { "" };
+        writeln!(self.elements, r#"<circle r="1" transform="matrix({} {} {} {} {} {})" {}{}/>"#,
+                 axis1.x, axis1.y, axis2.x, axis2.y, centre.x, centre.y,
+                 fill_and_stroke(style), non_scaling).unwrap();
+    }
+
+    // Embedded as a PNG, stretched over its own pixel size and then mapped into the frame
+    fn draw_image(&mut self, image: &Image, frame: &Frame, opacity: f32) {
+        let (width, height) = (image.width() as f32, image.height() as f32);
+        let (origin, edge1, edge2) = (frame.origin(), frame.edge1(), frame.edge2());
+        let opacity = if opacity < 1.0 { format!(r#" opacity="{}""#, opacity) } else { String::new() };
+        writeln!(self.elements,
+                 r#"<image width="{}" height="{}" preserveAspectRatio="none" transform="matrix({} {} {} {} {} {})"{} href="data:image/png;base64,{}"/>"#,
+                 width, height, edge1.x / width, edge1.y / width, edge2.x / height, edge2.y / height,
+                 origin.x, origin.y, opacity, image.to_png_base64()).unwrap();
+    }
+
+    // The viewer's own sans-serif font, so the text's width is only approximately the bitmap font's
+    fn draw_text(&mut self, text: &str, position: &Vector, size: f32, anchor: Anchor, style: &Style) {
+        let Some(colour) = style.text_colour() else { return };
+        let (across, down) = anchor.fractions();
+        let text_anchor = if across == 0.0 { "start" } else if across == 1.0 { "end" } else { "middle" };
+        let baseline = if down == 0.0 { "text-top" } else if down == 1.0 { "text-bottom" } else { "central" };
+        writeln!(self.elements,
+                 r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="{}" dominant-baseline="{}" {}>{}</text>"#,
+                 position.x, position.y, size, text_anchor, baseline, paint("fill", &colour), escape(text)).unwrap();
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// SVG arcs are given by their radii, rotation and end point. The conjugate axes are turned into
+// the principal ones with the singular value decomposition of the matrix they make. Arcs
+// of more than half a turn are split, so the large-arc flag is never needed.
+fn arc(centre: &Vector, axis1: &Vector, axis2: &Vector, start: f32, end: f32) -> String {
+    let (e, f) = ((axis1.x + axis2.y) / 2.0, (axis1.x - axis2.y) / 2.0);
+    let (g, h) = ((axis1.y + axis2.x) / 2.0, (axis1.y - axis2.x) / 2.0);
+    let (q, r) = ((e * e + h * h).sqrt(), (f * f + g * g).sqrt());
+    let (radius1, radius2) = (q + r, (q - r).abs());
+    let rotation = ((g.atan2(f) + h.atan2(e)) / 2.0).to_degrees();
+    let mirrored = axis1.x * axis2.y - axis1.y * axis2.x < 0.0;
+    let sweep = if (end > start) != mirrored { 1 } else { 0 };
+    let pieces = ((end - start).abs() / std::f32::consts::PI).ceil().max(1.0) as usize;
+    let mut commands = String::new();
+    for i in 1..=pieces {
+        let angle = start + (end - start) * i as f32 / pieces as f32;
+        let to = *centre + axis1.scale(angle.cos()) + axis2.scale(angle.sin());
+        write!(commands, " A{} {} {} 0 {} {}", radius1, radius2, rotation, sweep, point(&to)).unwrap();
+    }
+    commands
+}
+
+fn point(v: &Vector) -> String {
+    format!("{} {}", v.x, v.y)
+}
+
+// Attributes are left out where SVG's defaults already match the style's
+fn stroke(style: &Style) -> String {
+    let Some(colour) = style.stroke_colour() else { return r#"stroke="none""#.to_string() };
+    let mut attributes = format!(r#"stroke-width="{}" {}"#, style.width, paint("stroke", &colour));
+    match style.join {
+        LineJoin::Miter => {}
+        LineJoin::Round => attributes += r#" stroke-linejoin="round""#,
+        LineJoin::Bevel => attributes += r#" stroke-linejoin="bevel""#,
+    }
+    match style.cap {
+        LineCap::Butt => {}
+        LineCap::Round => attributes += r#" stroke-linecap="round""#,
+        LineCap::Square => attributes += r#" stroke-linecap="square""#,
+    }
+    if let Some(pattern) = style.dash_pattern() {
+        let lengths: Vec<String> = pattern.iter().map(|length| length.to_string()).collect();
+        write!(attributes, r#" stroke-dasharray="{}""#, lengths.join(" ")).unwrap();
+    }
+    attributes
+}
+
+fn fill_and_stroke(style: &Style) -> String {
+    let fill = match style.fill_colour() {
+        Some(colour) => paint("fill", &colour),
+        None => r#"fill="none""#.to_string(),
+    };
+    match style.stroke {
+        Some(_) => format!("{} {}", fill, stroke(style)),
+        None => fill,
+    }
+}
+
+fn paint(attribute: &str, colour: &Colour) -> String {
+    let [r, g, b, _] = colour.to_int_rgba();
+    let rgb = format!(r#"{attribute}="rgb({r},{g},{b})""#);
+    if colour.a < 1.0 {
+        format!(r#"{rgb} {attribute}-opacity="{}""#, colour.a)
+    } else {
+        rgb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_element(shape: &Shape) -> String {
+        let mut svg = SvgRenderer::new(10, 10);
+        renderer::render_shape(shape, &mut svg);
+        svg.elements
+    }
+
+    #[test]
+    fn line() {
+        let shape = Shape::new_line(Segment::new(Vector::new(1.0, 2.0), Vector::new(3.5, 4.0)),
+                                    Style::stroke(2.0, Colour::RED));
+        assert_eq!(to_element(&shape),
+                   "<line x1=\"1\" y1=\"2\" x2=\"3.5\" y2=\"4\" stroke-width=\"2\" stroke=\"rgb(255,0,0)\"/>\n");
+    }
+
+    #[test]
+    fn poly_line() {
+        let v1 = Vector::new(0.0, 0.0);
+        let v2 = Vector::new(10.0, 0.0);
+        let v3 = Vector::new(10.0, 10.0);
+        let shape = Shape::new_poly_line(vec![v1, v2, v3], false, Style::stroke(1.0, Colour::BLACK));
+        assert_eq!(to_element(&shape),
+                   "<polyline points=\"0 0 10 0 10 10\" fill=\"none\" stroke-width=\"1\" stroke=\"rgb(0,0,0)\"/>\n");
+    }
+
+    #[test]
+    fn closed_poly_line() {
+        let style = Style::stroke(2.0, Colour::RED).with_join(LineJoin::Round).with_cap(LineCap::Square);
+        let shape = Shape::new_poly_line(vec![Vector::zero(), Vector::new(10.0, 0.0), Vector::new(10.0, 10.0)],
+                                         true, style);
+        assert_eq!(to_element(&shape),
+                   "<polygon points=\"0 0 10 0 10 10\" fill=\"none\" stroke-width=\"2\" stroke=\"rgb(255,0,0)\" \
+                   stroke-linejoin=\"round\" stroke-linecap=\"square\"/>\n");
+    }
+
+    #[test]
+    fn circle() {
+        let shape = Shape::new_circle(&Vector::new(50.0, 60.0), 5.0,
+                                      Style::fill(Colour::from_rgba(0.0, 1.0, 0.0, 0.5)));
+        assert_eq!(to_element(&shape),
+                   "<circle cx=\"50\" cy=\"60\" r=\"5\" fill=\"rgb(0,255,0)\" fill-opacity=\"0.5\"/>\n");
+    }
+
+    #[test]
+    fn ellipse() {
+        let shape = Shape::new_ellipse(&Vector::new(50.0, 60.0), Vector::new(10.0, 0.0),
+                                       Vector::new(2.0, 5.0), Style::fill(Colour::RED));
+        assert_eq!(to_element(&shape),
+                   "<circle r=\"1\" transform=\"matrix(10 0 2 5 50 60)\" fill=\"rgb(255,0,0)\"/>\n");
+    }
+
+    #[test]
+    fn outlined_ellipse() {
+        let shape = Shape::new_ellipse(&Vector::new(50.0, 60.0), Vector::new(10.0, 0.0),
+                                       Vector::new(0.0, 5.0), Style::stroke(2.0, Colour::BLUE));
+        assert_eq!(to_element(&shape),
+                   "<circle r=\"1\" transform=\"matrix(10 0 0 5 50 60)\" fill=\"none\" \
+                   stroke-width=\"2\" stroke=\"rgb(0,0,255)\" vector-effect=\"non-scaling-stroke\"/>\n");
+    }
+
+    #[test]
+    fn polygon() {
+        let shape = Shape::new_polygon(vec![Vector::zero(), Vector::new(10.0, 0.0), Vector::new(5.0, 8.5)],
+                                       Style::fill_and_stroke(Colour::YELLOW, 1.5, Colour::BLACK));
+        assert_eq!(to_element(&shape),
+                   "<polygon points=\"0 0 10 0 5 8.5\" fill=\"rgb(255,255,0)\" \
+                   stroke-width=\"1.5\" stroke=\"rgb(0,0,0)\"/>\n");
+    }
+
+    #[test]
+    fn rect() {
+        let shape = Shape::new_rect(Vector::new(1.0, 2.0), Vector::new(4.0, 8.0), Style::fill(Colour::BLUE));
+        assert_eq!(to_element(&shape),
+                   "<rect x=\"1\" y=\"2\" width=\"3\" height=\"6\" fill=\"rgb(0,0,255)\"/>\n");
+    }
+
+    #[test]
+    fn path() {
+        let curves = vec![
+            Curve::Quadratic(Vector::zero(), Vector::new(5.0, 10.0), Vector::new(10.0, 0.0)),
+            Curve::Cubic(Vector::new(10.0, 0.0), Vector::new(10.0, 5.0), Vector::new(5.0, 5.0), Vector::new(5.0, 2.0)),
+            Curve::Line(Vector::new(4.0, 2.0), Vector::new(0.0, 2.0)),
+        ];
+        let shape = Shape::new_path(curves, true, Style::fill(Colour::RED));
+        assert_eq!(to_element(&shape),
+                   "<path d=\"M0 0 Q5 10 10 0 C10 5 5 5 5 2 L4 2 L0 2 Z\" fill=\"rgb(255,0,0)\"/>\n");
+    }
+
+    #[test]
+    fn arc_path() {
+        let quarter = Curve::Arc(Vector::zero(), Vector::new(10.0, 0.0), Vector::new(0.0, 10.0),
+                                 0.0, std::f32::consts::FRAC_PI_2);
+        let shape = Shape::new_path(vec![quarter], false, Style::stroke(1.0, Colour::BLACK));
+        let element = to_element(&shape);
+        assert!(element.starts_with("<path d=\"M10 0 A10 10 0 0 1 "), "{}", element);
+        // A stretched whole turn is two half turns, with the long axis as the first radius
+        let stretched = Curve::Arc(Vector::zero(), Vector::new(0.0, 2.0), Vector::new(-8.0, 0.0),
+                                   0.0, -2.0 * std::f32::consts::PI);
+        let element = to_element(&Shape::new_path(vec![stretched], false, Style::stroke(1.0, Colour::BLACK)));
+        assert_eq!(element.matches(" A8 2 0 0 0 ").count(), 2, "{}", element);
+    }
+
+    #[test]
+    fn dashed_translucent_line() {
+        let style = Style::stroke(1.0, Colour::BLACK).with_dash(vec![4.0, 2.0]).with_opacity(0.5);
+        let shape = Shape::new_line(Segment::new(Vector::zero(), Vector::new(10.0, 0.0)), style);
+        assert_eq!(to_element(&shape),
+                   "<line x1=\"0\" y1=\"0\" x2=\"10\" y2=\"0\" stroke-width=\"1\" stroke=\"rgb(0,0,0)\" \
+                   stroke-opacity=\"0.5\" stroke-dasharray=\"4 2\"/>\n");
+    }
+
+    #[test]
+    fn text() {
+        let shape = Shape::new_text("a < b & c", Vector::new(5.0, 6.0), 12.0, Anchor::Centre,
+                                    Style::fill(Colour::BLUE));
+        assert_eq!(to_element(&shape),
+                   "<text x=\"5\" y=\"6\" font-family=\"sans-serif\" font-size=\"12\" text-anchor=\"middle\" \
+                   dominant-baseline=\"central\" fill=\"rgb(0,0,255)\">a &lt; b &amp; c</text>\n");
+    }
+
+    #[test]
+    fn text_anchors() {
+        let shape = Shape::new_text("x", Vector::zero(), 10.0, Anchor::BottomRight, Style::fill(Colour::BLACK));
+        let element = to_element(&shape);
+        assert!(element.contains("text-anchor=\"end\" dominant-baseline=\"text-bottom\""), "{}", element);
+    }
+
+    #[test]
+    fn image() {
+        let image = std::rc::Rc::new(Image::new(2, 1, vec![[255, 0, 0, 255], [0, 0, 255, 255]]));
+        let frame = Frame::new(Vector::new(10.0, 20.0), Vector::new(0.0, 100.0), Vector::new(-50.0, 0.0));
+        let element = to_element(&Shape::new_image(&image, frame));
+        assert!(element.starts_with("<image width=\"2\" height=\"1\" preserveAspectRatio=\"none\" \
+                                    transform=\"matrix(0 50 -50 0 10 20)\" href=\"data:image/png;base64,iVBORw0KGgo"),
+                "{}", element);
+    }
+
+    #[test]
+    fn document() {
+        let svg = to_svg(&[Shape::new_circle(&Vector::zero(), 1.0, Style::fill(Colour::BLUE))], 100, 50,
+                         CoordinateSystem::YDown);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\""));
+        assert!(svg.contains("<rect width=\"100\" height=\"50\" fill=\"rgb(255,255,255)\"/>"));
+        assert!(svg.contains("<circle "));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn document_with_y_up() {
+        let svg = to_svg(&[Shape::new_circle(&Vector::new(10.0, 0.0), 1.0, Style::fill(Colour::BLUE))], 100, 50,
+                         CoordinateSystem::YUp);
+        assert!(svg.contains("<circle cx=\"10\" cy=\"50\" r=\"1\" "), "{}", svg);
+    }
+
+    #[test]
+    fn image_with_y_up() {
+        // Moved to the top of the document, but still with its first row at the top
+        let image = std::rc::Rc::new(Image::new(2, 1, vec![[255, 0, 0, 255], [0, 0, 255, 255]]));
+        let frame = Frame::new(Vector::new(10.0, 20.0), Vector::new(20.0, 0.0), Vector::new(0.0, 10.0));
+        let svg = to_svg(&[Shape::new_image(&image, frame)], 100, 50, CoordinateSystem::YUp);
+        assert!(svg.contains("transform=\"matrix(10 0 0 10 10 20)\""), "{}", svg);
+        assert!(!svg.contains("opacity"), "{}", svg);
+        let svg = to_svg(&[Shape::Image(image, frame, 0.5)], 100, 50, CoordinateSystem::YUp);
+        assert!(svg.contains("transform=\"matrix(10 0 0 10 10 20)\" opacity=\"0.5\""), "{}", svg);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::painter::Painter;
+use crate::picture_language::vector::Vector;
+
+// Places copies of a painter's frame in rows and columns. Each column moves along by
+// column_step and each row by row_step; odd rows and odd columns are also shifted,
+// which gives brick and hexagonal layouts.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Tessellation {
+    pub column_step: Vector,
+    pub row_step: Vector,
+    pub odd_row_offset: Vector,
+    pub odd_column_offset: Vector,
+}
+
+impl Tessellation {
+    pub fn new(column_step: Vector, row_step: Vector) -> Self {
+        Tessellation { column_step, row_step, odd_row_offset: Vector::zero(), odd_column_offset: Vector::zero() }
+    }
+
+    // Edge to edge, so a parallelogram frame tiles the plane
+    pub fn grid(frame: &Frame) -> Self {
+        Tessellation::new(frame.edge1(), frame.edge2())
+    }
+
+    // Every other row is shifted along by half a tile
+    pub fn brick(frame: &Frame) -> Self {
+        Tessellation::grid(frame).with_odd_row_offset(frame.edge1().scale(0.5))
+    }
+
+    // For a flat-topped hexagon touching all four sides of the frame: columns overlap by
+    // a quarter of the frame's width and every other column drops by half a tile
+    pub fn hexagonal(frame: &Frame) -> Self {
+        Tessellation::new(frame.edge1().scale(0.75), frame.edge2())
+            .with_odd_column_offset(frame.edge2().scale(0.5))
+    }
+
+    pub fn with_odd_row_offset(self, offset: Vector) -> Self {
+        Tessellation { odd_row_offset: offset, ..self }
+    }
+
+    pub fn with_odd_column_offset(self, offset: Vector) -> Self {
+        Tessellation { odd_column_offset: offset, ..self }
+    }
+
+    pub fn offset(&self, row: usize, column: usize) -> Vector {
+        let mut offset = self.column_step.scale(column as f32) + self.row_step.scale(row as f32);
+        if row % 2 == 1 {
+            offset = offset + self.odd_row_offset;
+        }
+        if column % 2 == 1 {
+            offset = offset + self.odd_column_offset;
+        }
+        offset
+    }
+
+    // Row by row, from the painter's own frame at row 0, column 0
+    pub fn tessellate(&self, painter: &Painter, rows: usize, columns: usize) -> CompositePainter {
+        let painters = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .map(|(row, column)| painter.with_frame(painter.frame.transform(self.offset(row, column))))
+            .collect();
+        CompositePainter::new(painters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> Frame {
+        Frame::new(Vector::new(10.0, 20.0),
+                   Vector::new(100.0, 0.0),
+                   Vector::new(0.0, 50.0))
+    }
+
+    fn origins(composite: &CompositePainter) -> Vec<Vector> {
+        composite.painters().iter().map(|p| p.frame.origin()).collect()
+    }
+
+    #[test]
+    fn tessellate_along_a_vector() {
+        let tiles = Painter::new(frame()).tessellate(Vector::new(5.0, 7.0), 3);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(15.0, 27.0), Vector::new(20.0, 34.0),
+        ]);
+    }
+
+    #[test]
+    fn tessellate_to_right() {
+        let skewed = Frame::new(Vector::new(10.0, 20.0),
+                                Vector::new(100.0, 20.0),
+                                Vector::new(20.0, 200.0));
+        let tiles = Painter::new(skewed).tessellate_to_right(3);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(110.0, 40.0), Vector::new(210.0, 60.0),
+        ]);
+    }
+
+    #[test]
+    fn tessellate_to_down() {
+        let tiles = Painter::new(frame()).tessellate_to_down(3);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(10.0, 70.0), Vector::new(10.0, 120.0),
+        ]);
+    }
+
+    #[test]
+    fn grid() {
+        let tiles = Painter::new(frame()).tessellate_grid(2, 3);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(110.0, 20.0), Vector::new(210.0, 20.0),
+            Vector::new(10.0, 70.0), Vector::new(110.0, 70.0), Vector::new(210.0, 70.0),
+        ]);
+        assert!(tiles.painters().iter().all(|p| p.frame.edge1() == frame().edge1()));
+    }
+
+    #[test]
+    fn brick() {
+        let tiles = Painter::new(frame()).tessellate_brick(3, 2);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(110.0, 20.0),
+            Vector::new(60.0, 70.0), Vector::new(160.0, 70.0),
+            Vector::new(10.0, 120.0), Vector::new(110.0, 120.0),
+        ]);
+    }
+
+    #[test]
+    fn hexagonal() {
+        let tiles = Painter::new(frame()).tessellate_hexagonal(2, 3);
+        assert_eq!(origins(&tiles), vec![
+            Vector::new(10.0, 20.0), Vector::new(85.0, 45.0), Vector::new(160.0, 20.0),
+            Vector::new(10.0, 70.0), Vector::new(85.0, 95.0), Vector::new(160.0, 70.0),
+        ]);
+    }
+
+    #[test]
+    fn configurable_offsets() {
+        let tessellation = Tessellation::new(Vector::new(30.0, 0.0), Vector::new(0.0, 40.0))
+            .with_odd_row_offset(Vector::new(-10.0, 0.0))
+            .with_odd_column_offset(Vector::new(0.0, 5.0));
+        assert_eq!(tessellation.offset(0, 0), Vector::zero());
+        assert_eq!(tessellation.offset(1, 0), Vector::new(-10.0, 40.0));
+        assert_eq!(tessellation.offset(0, 1), Vector::new(30.0, 5.0));
+        assert_eq!(tessellation.offset(1, 1), Vector::new(20.0, 45.0));
+        assert_eq!(tessellation.offset(2, 2), Vector::new(60.0, 80.0));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Painter::new(frame()).tessellate_grid(0, 3).painters().is_empty());
+        assert!(Painter::new(frame()).tessellate(Vector::one(), 0).painters().is_empty());
+    }
+}
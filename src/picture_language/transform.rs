@@ -0,0 +1,132 @@
+use crate::picture_language::frame::Frame;
+use crate::picture_language::vector::Vector;
+
+// An affine map, p -> x_axis * p.x + y_axis * p.y + offset: the 2x3 matrix
+// [x_axis y_axis offset]. A frame is the transform from its unit square to the device.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transform {
+    pub x_axis: Vector,
+    pub y_axis: Vector,
+    pub offset: Vector,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        x_axis: Vector { x: 1.0, y: 0.0 },
+        y_axis: Vector { x: 0.0, y: 1.0 },
+        offset: Vector { x: 0.0, y: 0.0 },
+    };
+
+    pub fn new(x_axis: Vector, y_axis: Vector, offset: Vector) -> Self {
+        Transform { x_axis, y_axis, offset }
+    }
+
+    pub fn translate(offset: Vector) -> Self {
+        Transform { offset, ..Transform::IDENTITY }
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Transform::new(Vector::new(sx, 0.0), Vector::new(0.0, sy), Vector::zero())
+    }
+
+    // Turns the x axis towards the y axis, which is clockwise on screen where y points down
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform::new(Vector::new(cos, sin), Vector::new(-sin, cos), Vector::zero())
+    }
+
+    // x moves by x_shear * y, and y by y_shear * x
+    pub fn shear(x_shear: f32, y_shear: f32) -> Self {
+        Transform::new(Vector::new(1.0, y_shear), Vector::new(x_shear, 1.0), Vector::zero())
+    }
+
+    pub fn apply(&self, p: Vector) -> Vector {
+        self.apply_vector(p) + self.offset
+    }
+
+    // Directions and lengths are not moved by the offset
+    pub fn apply_vector(&self, v: Vector) -> Vector {
+        self.x_axis.scale(v.x) + self.y_axis.scale(v.y)
+    }
+
+    // This transform followed by next
+    pub fn then(&self, next: &Transform) -> Self {
+        Transform::new(next.apply_vector(self.x_axis), next.apply_vector(self.y_axis), next.apply(self.offset))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.x_axis.x * self.y_axis.y - self.x_axis.y * self.y_axis.x
+    }
+
+    // None when the transform squashes everything onto a line or a point
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let x_axis = Vector::new(self.y_axis.y / determinant, -self.x_axis.y / determinant);
+        let y_axis = Vector::new(-self.y_axis.x / determinant, self.x_axis.x / determinant);
+        let inverse = Transform::new(x_axis, y_axis, Vector::zero());
+        Some(Transform { offset: inverse.apply_vector(self.offset).scale(-1.0), ..inverse })
+    }
+}
+
+impl From<Frame> for Transform {
+    fn from(frame: Frame) -> Self {
+        Transform::new(frame.edge1(), frame.edge2(), frame.origin())
+    }
+}
+
+impl From<Transform> for Frame {
+    fn from(transform: Transform) -> Self {
+        Frame::new(transform.offset, transform.x_axis, transform.y_axis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn close(v1: Vector, v2: Vector) -> bool {
+        (v1 - v2).length() < 1e-5
+    }
+
+    #[test]
+    fn constructors() {
+        let p = Vector::new(2.0, 3.0);
+        assert_eq!(Transform::IDENTITY.apply(p), p);
+        assert_eq!(Transform::translate(Vector::new(1.0, -1.0)).apply(p), Vector::new(3.0, 2.0));
+        assert_eq!(Transform::scale(2.0, 10.0).apply(p), Vector::new(4.0, 30.0));
+        assert!(close(Transform::rotate(FRAC_PI_2).apply(p), Vector::new(-3.0, 2.0)));
+        assert_eq!(Transform::shear(1.0, 0.0).apply(p), Vector::new(5.0, 3.0));
+        assert_eq!(Transform::translate(Vector::one()).apply_vector(p), p);
+    }
+
+    #[test]
+    fn then_applies_in_order() {
+        let scale_then_move = Transform::scale(2.0, 2.0).then(&Transform::translate(Vector::new(10.0, 0.0)));
+        assert_eq!(scale_then_move.apply(Vector::one()), Vector::new(12.0, 2.0));
+        let move_then_scale = Transform::translate(Vector::new(10.0, 0.0)).then(&Transform::scale(2.0, 2.0));
+        assert_eq!(move_then_scale.apply(Vector::one()), Vector::new(22.0, 2.0));
+    }
+
+    #[test]
+    fn inverse() {
+        let transform = Transform::new(Vector::new(100.0, 50.0), Vector::new(-20.0, 200.0), Vector::new(10.0, 20.0));
+        let inverse = transform.inverse().unwrap();
+        let p = Vector::new(0.5, 0.25);
+        assert!(close(inverse.apply(transform.apply(p)), p));
+        assert!(close(transform.apply(inverse.apply(p)), p));
+        assert_eq!(Transform::scale(1.0, 0.0).inverse(), None);
+    }
+
+    #[test]
+    fn frame_conversion() {
+        let frame = Frame::new(Vector::new(10.0, 20.0), Vector::new(100.0, 50.0), Vector::new(-20.0, 200.0));
+        let transform = Transform::from(frame);
+        assert_eq!(transform.apply(Vector::new(0.5, 0.25)), frame.map(Vector::new(0.5, 0.25)));
+        assert_eq!(Frame::from(transform), frame);
+        assert!(close(transform.inverse().unwrap().apply(Vector::new(90.0, 270.0)), Vector::one()));
+    }
+}
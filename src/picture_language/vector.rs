@@ -38,6 +38,19 @@ impl Vector {
     pub fn scale(&self, s: f32) -> Self {
         Vector { x: self.x * s, y: self.y * s }
     }
+
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn dot(&self, other: Vector) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // The z of the 3D cross product: positive when other is anticlockwise of this with y up
+    pub fn cross(&self, other: Vector) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
 }
 
 #[cfg(test)]
@@ -56,6 +69,19 @@ mod tests {
                    Vector::new(-2.0, -2.0));
     }
 
+    #[test]
+    fn length() {
+        assert_eq!(Vector::new(3.0, -4.0).length(), 5.0);
+    }
+
+    #[test]
+    fn dot_and_cross() {
+        let (v1, v2) = (Vector::new(1.0, 2.0), Vector::new(3.0, 4.0));
+        assert_eq!(v1.dot(v2), 11.0);
+        assert_eq!(v1.cross(v2), -2.0);
+        assert_eq!(v2.cross(v1), 2.0);
+    }
+
     #[test]
     fn scale() {
         assert_eq!(Vector::new(1.0, 2.0).scale(10.0),
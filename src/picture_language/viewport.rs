@@ -0,0 +1,187 @@
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::coordinates::CoordinateSystem;
+use crate::picture_language::transform::Transform;
+use crate::picture_language::vector::Vector;
+
+// Zooming is kept within these, so a picture can neither vanish nor swallow the window
+const MIN_SCALE: f32 = 1e-3;
+const MAX_SCALE: f32 = 1e3;
+
+// How a picture is shown in a window: the picture is scaled, flipped if its y points up, and
+// then moved by the offset, in window pixels. It starts with the picture's origin in the top
+// left corner of the window, or the bottom left if y points up, and a pixel for each unit.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Viewport {
+    size: Vector,
+    coordinates: CoordinateSystem,
+    scale: f32,
+    offset: Vector,
+}
+
+impl Viewport {
+    pub fn new(width: f32, height: f32) -> Self {
+        Viewport {
+            size: Vector::new(width, height),
+            coordinates: CoordinateSystem::YDown,
+            scale: 1.0,
+            offset: Vector::zero(),
+        }
+    }
+
+    pub fn with_coordinates(self, coordinates: CoordinateSystem) -> Self {
+        let mut viewport = Viewport { coordinates, ..self };
+        viewport.reset();
+        viewport
+    }
+
+    pub fn coordinates(&self) -> CoordinateSystem {
+        self.coordinates
+    }
+
+    pub fn size(&self) -> Vector {
+        self.size
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    // From the picture to the window
+    pub fn transform(&self) -> Transform {
+        Transform::scale(self.scale, self.y_scale()).then(&Transform::translate(self.offset))
+    }
+
+    fn y_scale(&self) -> f32 {
+        match self.coordinates {
+            CoordinateSystem::YDown => self.scale,
+            CoordinateSystem::YUp => -self.scale,
+        }
+    }
+
+    // The transform without the offset
+    fn scaled(&self, p: Vector) -> Vector {
+        Vector::new(p.x * self.scale, p.y * self.y_scale())
+    }
+
+    // The point of the picture under a point of the window, such as the mouse
+    pub fn picture_point(&self, window_point: Vector) -> Vector {
+        self.transform().inverse().unwrap().apply(window_point)
+    }
+
+    // The picture stays where it is in the window's top left corner, or bottom left if y points up
+    pub fn resize(&mut self, width: f32, height: f32) {
+        if self.coordinates == CoordinateSystem::YUp {
+            self.offset.y += height - self.size.y;
+        }
+        self.size = Vector::new(width, height);
+    }
+
+    pub fn pan(&mut self, delta: Vector) {
+        self.offset = self.offset + delta;
+    }
+
+    // Zooms in for factors above 1, keeping the picture under the window point still
+    pub fn zoom_at(&mut self, window_point: Vector, factor: f32) {
+        let fixed = self.picture_point(window_point);
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.offset = window_point - self.scaled(fixed);
+    }
+
+    pub fn zoom_at_centre(&mut self, factor: f32) {
+        self.zoom_at(self.size.scale(0.5), factor);
+    }
+
+    // Scales the bounds to fill the window, less a margin of pixels on every side, and
+    // centres them. Bounds with no area are centred without scaling.
+    pub fn fit(&mut self, bounds: &Bounds, margin: f32) {
+        let room = self.size - Vector::new(2.0 * margin, 2.0 * margin);
+        let scale = (room.x / bounds.width()).min(room.y / bounds.height());
+        self.scale = if scale.is_finite() && scale > 0.0 { scale.clamp(MIN_SCALE, MAX_SCALE) } else { 1.0 };
+        self.offset = self.size.scale(0.5) - self.scaled(bounds.centre());
+    }
+
+    pub fn reset(&mut self) {
+        self.scale = 1.0;
+        self.offset = self.coordinates.to_device(self.size.y).offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(v1: Vector, v2: Vector) -> bool {
+        (v1 - v2).length() < 1e-3
+    }
+
+    #[test]
+    fn starts_as_the_identity() {
+        let viewport = Viewport::new(800.0, 600.0);
+        assert_eq!(viewport.transform(), Transform::IDENTITY);
+        assert_eq!(viewport.picture_point(Vector::new(10.0, 20.0)), Vector::new(10.0, 20.0));
+    }
+
+    #[test]
+    fn fit() {
+        let mut viewport = Viewport::new(800.0, 600.0);
+        let bounds = Bounds::new(Vector::new(1000.0, -100.0), Vector::new(3800.0, 900.0));
+        viewport.fit(&bounds, 50.0);
+        assert_eq!(viewport.scale(), 0.25);
+        let transform = viewport.transform();
+        assert!(close(transform.apply(bounds.centre()), Vector::new(400.0, 300.0)));
+        assert!(close(transform.apply(bounds.min), Vector::new(50.0, 175.0)));
+        assert!(close(transform.apply(bounds.max), Vector::new(750.0, 425.0)));
+    }
+
+    #[test]
+    fn fit_a_point() {
+        let mut viewport = Viewport::new(800.0, 600.0);
+        viewport.fit(&Bounds::new(Vector::one(), Vector::one()), 10.0);
+        assert_eq!(viewport.scale(), 1.0);
+        assert!(close(viewport.transform().apply(Vector::one()), Vector::new(400.0, 300.0)));
+    }
+
+    #[test]
+    fn zoom_keeps_the_point_still() {
+        let mut viewport = Viewport::new(800.0, 600.0);
+        viewport.pan(Vector::new(30.0, -20.0));
+        let mouse = Vector::new(200.0, 100.0);
+        let under_mouse = viewport.picture_point(mouse);
+        viewport.zoom_at(mouse, 2.0);
+        assert_eq!(viewport.scale(), 2.0);
+        assert!(close(viewport.transform().apply(under_mouse), mouse));
+        assert!(close(viewport.picture_point(mouse), under_mouse));
+        viewport.zoom_at(mouse, 1e9);
+        assert_eq!(viewport.scale(), MAX_SCALE);
+    }
+
+    #[test]
+    fn pan_and_reset() {
+        let mut viewport = Viewport::new(800.0, 600.0);
+        viewport.pan(Vector::new(30.0, -20.0));
+        viewport.zoom_at_centre(0.5);
+        assert!(close(viewport.transform().apply(Vector::new(800.0, 600.0)), Vector::new(615.0, 440.0)));
+        viewport.resize(400.0, 400.0);
+        viewport.reset();
+        assert_eq!(viewport, Viewport::new(400.0, 400.0));
+    }
+
+    #[test]
+    fn y_up() {
+        let mut viewport = Viewport::new(800.0, 600.0).with_coordinates(CoordinateSystem::YUp);
+        assert_eq!(viewport.transform(), CoordinateSystem::YUp.to_device(600.0));
+        assert_eq!(viewport.picture_point(Vector::new(10.0, 590.0)), Vector::new(10.0, 10.0));
+        viewport.resize(800.0, 400.0);
+        assert_eq!(viewport.transform().apply(Vector::zero()), Vector::new(0.0, 400.0));
+
+        let mouse = Vector::new(200.0, 100.0);
+        let under_mouse = viewport.picture_point(mouse);
+        viewport.zoom_at(mouse, 2.0);
+        assert!(close(viewport.transform().apply(under_mouse), mouse));
+
+        let bounds = Bounds::new(Vector::zero(), Vector::new(100.0, 50.0));
+        viewport.fit(&bounds, 0.0);
+        assert!(close(viewport.transform().apply(bounds.min), Vector::new(0.0, 400.0)));
+        assert!(close(viewport.transform().apply(bounds.max), Vector::new(800.0, 0.0)));
+    }
+}
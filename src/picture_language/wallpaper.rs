@@ -0,0 +1,247 @@
+use crate::picture_language::composite_painter::CompositePainter;
+use crate::picture_language::frame::Frame;
+use crate::picture_language::painter::Painter;
+use crate::picture_language::vector::Vector;
+
+// The 17 plane symmetry groups, in the international (Hermann-Mauguin) notation
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WallpaperGroup {
+    P1,
+    P2,
+    Pm,
+    Pg,
+    Cm,
+    Pmm,
+    Pmg,
+    Pgg,
+    Cmm,
+    P4,
+    P4m,
+    P4g,
+    P3,
+    P3m1,
+    P31m,
+    P6,
+    P6m,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Lattice {
+    Oblique,
+    Rectangular,
+    CentredRectangular,
+    Square,
+    Hexagonal,
+}
+
+// The motif is drawn in the unit square, which is placed at [0, 0.5] x [0, 0.5] of the lattice cell.
+// Copies only avoid overlapping when the motif stays within the group's fundamental region.
+const MOTIF_SCALE: f32 = 0.5;
+
+impl WallpaperGroup {
+    pub fn all() -> [WallpaperGroup; 17] {
+        use WallpaperGroup::*;
+        [P1, P2, Pm, Pg, Cm, Pmm, Pmg, Pgg, Cmm, P4, P4m, P4g, P3, P3m1, P31m, P6, P6m]
+    }
+
+    pub fn lattice(&self) -> Lattice {
+        use WallpaperGroup::*;
+        match self {
+            P1 | P2 => Lattice::Oblique,
+            Pm | Pg | Pmm | Pmg | Pgg => Lattice::Rectangular,
+            Cm | Cmm => Lattice::CentredRectangular,
+            P4 | P4m | P4g => Lattice::Square,
+            P3 | P3m1 | P31m | P6 | P6m => Lattice::Hexagonal,
+        }
+    }
+
+    // A lattice cell of the right shape for this group, with its first edge of the given length
+    pub fn cell(&self, origin: Vector, size: f32) -> Frame {
+        let edge2 = match self.lattice() {
+            Lattice::Oblique => Vector::new(0.3, 0.9),
+            Lattice::Rectangular | Lattice::CentredRectangular => Vector::new(0.0, 0.75),
+            Lattice::Square => Vector::new(0.0, 1.0),
+            Lattice::Hexagonal => Vector::new(-0.5, 3.0_f32.sqrt() / 2.0),
+        };
+        Frame::new(origin, Vector::new(size, 0.0), edge2.scale(size))
+    }
+
+    // The symmetry operations of one cell, each as a frame over the cell's unit square.
+    // These are the general positions of the International Tables for Crystallography,
+    // including the centring translation for cm and cmm.
+    pub fn operations(&self) -> Vec<Frame> {
+        use WallpaperGroup::*;
+        let identity = [[1.0, 0.0, 0.0, 1.0, 0.0, 0.0]];
+        let rotate2 = [[-1.0, 0.0, 0.0, -1.0, 0.0, 0.0]];
+        let rotate4 = [[0.0, -1.0, 1.0, 0.0, 0.0, 0.0], [0.0, 1.0, -1.0, 0.0, 0.0, 0.0]];
+        let rotate3 = [[0.0, -1.0, 1.0, -1.0, 0.0, 0.0], [-1.0, 1.0, -1.0, 0.0, 0.0, 0.0]];
+        let rotate6 = [[0.0, 1.0, -1.0, 1.0, 0.0, 0.0], [1.0, -1.0, 1.0, 0.0, 0.0, 0.0]];
+        let mirror_x = [[-1.0, 0.0, 0.0, 1.0, 0.0, 0.0]];
+        let mirror_y = [[1.0, 0.0, 0.0, -1.0, 0.0, 0.0]];
+        let mirror_diagonals = [[0.0, 1.0, 1.0, 0.0, 0.0, 0.0], [0.0, -1.0, -1.0, 0.0, 0.0, 0.0]];
+        let hexagonal_mirrors_1 = [[0.0, -1.0, -1.0, 0.0, 0.0, 0.0], [-1.0, 1.0, 0.0, 1.0, 0.0, 0.0],
+            [1.0, 0.0, 1.0, -1.0, 0.0, 0.0]];
+        let hexagonal_mirrors_2 = [[0.0, 1.0, 1.0, 0.0, 0.0, 0.0], [1.0, -1.0, 0.0, -1.0, 0.0, 0.0],
+            [-1.0, 0.0, -1.0, 1.0, 0.0, 0.0]];
+        let rows: Vec<[f32; 6]> = match self {
+            P1 => [identity].concat(),
+            P2 => [&identity[..], &rotate2].concat(),
+            Pm => [&identity[..], &mirror_x].concat(),
+            Pg => [&identity[..], &glide(&mirror_x, 0.0, 0.5)].concat(),
+            Cm => centred(&[&identity[..], &mirror_x].concat()),
+            Pmm => [&identity[..], &rotate2, &mirror_x, &mirror_y].concat(),
+            Pmg => [&identity[..], &rotate2, &glide(&mirror_x, 0.5, 0.0), &glide(&mirror_y, 0.5, 0.0)].concat(),
+            Pgg => [&identity[..], &rotate2, &glide(&mirror_x, 0.5, 0.5), &glide(&mirror_y, 0.5, 0.5)].concat(),
+            Cmm => centred(&[&identity[..], &rotate2, &mirror_x, &mirror_y].concat()),
+            P4 => [&identity[..], &rotate2, &rotate4].concat(),
+            P4m => [&identity[..], &rotate2, &rotate4, &mirror_x, &mirror_y, &mirror_diagonals].concat(),
+            P4g => [&identity[..], &rotate2, &rotate4,
+                &glide(&[&mirror_x[..], &mirror_y, &mirror_diagonals].concat(), 0.5, 0.5)].concat(),
+            P3 => [&identity[..], &rotate3].concat(),
+            P3m1 => [&identity[..], &rotate3, &hexagonal_mirrors_1].concat(),
+            P31m => [&identity[..], &rotate3, &hexagonal_mirrors_2].concat(),
+            P6 => [&identity[..], &rotate3, &rotate2, &rotate6].concat(),
+            P6m => [&identity[..], &rotate3, &rotate2, &rotate6, &hexagonal_mirrors_1, &hexagonal_mirrors_2].concat(),
+        };
+        rows.iter()
+            .map(|[a, b, c, d, x, y]| Frame::new(Vector::new(*x, *y), Vector::new(*a, *c), Vector::new(*b, *d)))
+            .collect()
+    }
+
+    // Where each copy of the motif goes within the cell's unit square. Copies that the
+    // operations move outside the cell are brought back in by a lattice translation.
+    pub fn motif_frames(&self) -> Vec<Frame> {
+        let motif = Frame::new(Vector::zero(), Vector::new(MOTIF_SCALE, 0.0), Vector::new(0.0, MOTIF_SCALE));
+        self.operations().iter()
+            .map(|operation| {
+                let placed = operation.map_frame(&motif);
+                let centre = placed.map(Vector::new(0.5, 0.5));
+                placed.transform(Vector::new(-centre.x.floor(), -centre.y.floor()))
+            })
+            .collect()
+    }
+
+    // A painter for every copy of the motif, over rows x columns cells. Render the motif into the result.
+    pub fn tile(&self, cell: &Frame, rows: usize, columns: usize) -> CompositePainter {
+        let motifs = self.motif_frames();
+        let painters = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .flat_map(|(row, column)| {
+                let translate = Vector::new(column as f32, row as f32);
+                motifs.iter()
+                    .map(move |motif| Painter::new(cell.map_frame(&motif.transform(translate))))
+            })
+            .collect();
+        CompositePainter::new(painters)
+    }
+}
+
+fn glide(rows: &[[f32; 6]], x: f32, y: f32) -> Vec<[f32; 6]> {
+    rows.iter().map(|[a, b, c, d, tx, ty]| [*a, *b, *c, *d, tx + x, ty + y]).collect()
+}
+
+fn centred(rows: &[[f32; 6]]) -> Vec<[f32; 6]> {
+    [rows, &glide(rows, 0.5, 0.5)].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WallpaperGroup::*;
+
+    fn order(group: WallpaperGroup) -> usize {
+        match group {
+            P1 => 1,
+            P2 | Pm | Pg => 2,
+            P3 => 3,
+            Pmm | Pmg | Pgg | Cm | P4 => 4,
+            P3m1 | P31m | P6 => 6,
+            Cmm | P4m | P4g => 8,
+            P6m => 12,
+        }
+    }
+
+    fn same_up_to_lattice(f1: &Frame, f2: &Frame) -> bool {
+        let difference = f1.origin() - f2.origin();
+        f1.edge1() == f2.edge1() && f1.edge2() == f2.edge2() &&
+            difference.x == difference.x.round() && difference.y == difference.y.round()
+    }
+
+    #[test]
+    fn number_of_operations() {
+        WallpaperGroup::all().iter()
+            .for_each(|group| assert_eq!(group.operations().len(), order(*group), "{:?}", group));
+    }
+
+    #[test]
+    fn operations_form_a_group() {
+        for group in WallpaperGroup::all() {
+            let operations = group.operations();
+            for o1 in &operations {
+                for o2 in &operations {
+                    let composed = o1.map_frame(o2);
+                    assert!(operations.iter().any(|o| same_up_to_lattice(o, &composed)),
+                            "{:?} is not closed", group);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn operations_are_distinct() {
+        for group in WallpaperGroup::all() {
+            let operations = group.operations();
+            for (i, o1) in operations.iter().enumerate() {
+                assert!(operations.iter().skip(i + 1).all(|o2| !same_up_to_lattice(o1, o2)),
+                        "{:?} repeats {:?}", group, o1);
+            }
+        }
+    }
+
+    #[test]
+    fn motifs_are_within_the_cell() {
+        for group in WallpaperGroup::all() {
+            group.motif_frames().iter().for_each(|motif| {
+                let centre = motif.map(Vector::new(0.5, 0.5));
+                assert!((0.0..1.0).contains(&centre.x) && (0.0..1.0).contains(&centre.y),
+                        "{:?} {:?}", group, motif);
+            });
+        }
+    }
+
+    #[test]
+    fn p2_rotates_a_half_turn() {
+        assert_eq!(P2.motif_frames(), vec![
+            Frame::new(Vector::zero(), Vector::new(0.5, 0.0), Vector::new(0.0, 0.5)),
+            Frame::new(Vector::one(), Vector::new(-0.5, 0.0), Vector::new(0.0, -0.5)),
+        ]);
+    }
+
+    #[test]
+    fn pg_glides() {
+        assert_eq!(Pg.motif_frames()[1],
+                   Frame::new(Vector::new(1.0, 0.5), Vector::new(-0.5, 0.0), Vector::new(0.0, 0.5)));
+    }
+
+    #[test]
+    fn hexagonal_cell() {
+        let cell = P6.cell(Vector::zero(), 10.0);
+        let edge1 = cell.edge1();
+        let edge2 = cell.edge2();
+        let length2 = (edge2.x * edge2.x + edge2.y * edge2.y).sqrt();
+        assert!((length2 - 10.0).abs() < 1e-4);
+        assert!(((edge1.x * edge2.x + edge1.y * edge2.y) / 100.0 + 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn tile() {
+        let cell = P4.cell(Vector::new(10.0, 20.0), 100.0);
+        let tiles = P4.tile(&cell, 2, 3);
+        assert_eq!(tiles.painters().len(), 4 * 2 * 3);
+        assert_eq!(tiles.painters()[0].frame,
+                   Frame::new(Vector::new(10.0, 20.0), Vector::new(50.0, 0.0), Vector::new(0.0, 50.0)));
+        // Row 1, column 2 is translated by two cells across and one down
+        assert_eq!(tiles.painters()[4 * 5].frame,
+                   Frame::new(Vector::new(210.0, 120.0), Vector::new(50.0, 0.0), Vector::new(0.0, 50.0)));
+    }
+}
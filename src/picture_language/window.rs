@@ -1,72 +1,107 @@
-use std::time::Instant;
-use speedy2d::color::Color;
-use speedy2d::dimen::Vec2;
-use speedy2d::window::{WindowHandler, WindowHelper, MouseButton};
+use std::path::Path;
+use speedy2d::dimen::{UVec2, Vec2};
+use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode, WindowCreationOptions, WindowHandler,
+                       WindowHelper, WindowPosition, WindowSize};
 use speedy2d::{Graphics2D, Window};
-use crate::picture_language::segment::Segment;
-use crate::picture_language::shape::Shape;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::editor::Editor;
+use crate::picture_language::renderer::{self, Renderer};
 use crate::picture_language::vector::Vector;
 
-pub fn run_window() {
-    let window = Window::new_centered("Speedy2D: Animation", (800, 800)).unwrap();
+// An editor for tiles, kept in a picture file of segments such as tiles/boat.sexp, which is
+// opened if it is already there. The left button draws paths and the right one selects a
+// segment. Return or Escape finishes a path, Delete or Backspace removes the selected segment,
+// Ctrl+Z undoes, Ctrl+Y or Ctrl+Shift+Z redoes, and Ctrl+S saves.
+pub fn run_window(path: &str) {
+    let editor = match Editor::open(path, 800.0, 800.0) {
+        Ok(editor) => editor,
+        Err(error) => return eprintln!("{}", error),
+    };
+    let size = WindowSize::PhysicalPixels(UVec2::new(800, 800));
+    let options = WindowCreationOptions::new_windowed(size, Some(WindowPosition::Center)).with_resizable(true);
+    let window = Window::new_with_options(&format!("Tile editor: {}", path), options).unwrap();
     window.run_loop(MyWindowHandler {
-        start_time: Instant::now(),
-        shapes: vec![],
+        editor,
+        path: path.to_string(),
         mouse_position: Vector::new(0.0, 0.0),
-        last_click: Vector::new(0.0, 0.0),
+        modifiers: ModifiersState::default(),
     })
 }
 
 struct MyWindowHandler {
-    start_time: Instant,
-    shapes: Vec<Shape>,
+    editor: Editor,
+    path: String,
     mouse_position: Vector,
-    last_click: Vector,
+    modifiers: ModifiersState,
 }
 
-fn to_vec2(vec: &Vector) -> Vec2 {
-    Vec2 { x: vec.x, y: vec.y }
+impl MyWindowHandler {
+    // Shows how to use the tile as a painter, named after the file if that makes a symbol
+    fn save(&self) {
+        if let Err(error) = self.editor.save(&self.path) {
+            return eprintln!("{}: {}", self.path, error);
+        }
+        println!("Saved {}", self.path);
+        let name = Path::new(&self.path).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        if let Some(definition) = Editor::definition(name, &self.path) {
+            println!("{}", definition);
+        }
+    }
 }
 
-
 impl WindowHandler for MyWindowHandler {
-    fn on_draw(&mut self, helper: &mut WindowHelper, graphics: &mut Graphics2D) {
-        graphics.clear_screen(Color::WHITE);
-
-        for shape in &self.shapes {
-            match shape {
-                Shape::Line(ref segment, thickness, colour) =>
-                    graphics.draw_line(to_vec2(&segment.start), to_vec2(&segment.end),
-                                       *thickness, *colour),
-                Shape::PolyLine(lines, thickness, colour) => {
-                    lines.iter().for_each(|(from, to)|
-                        graphics.draw_line(to_vec2(from), to_vec2(to), *thickness, *colour));
-                }
-                Shape::Circle(centre, radius, colour) =>
-                    graphics.draw_circle(to_vec2(centre), *radius, *colour),
-            }
-        }
+    fn on_resize(&mut self, helper: &mut WindowHelper, size_pixels: UVec2) {
+        self.editor.resize(size_pixels.x as f32, size_pixels.y as f32);
+        helper.request_redraw();
+    }
 
-        let elapsed_secs = self.start_time.elapsed().as_secs_f32();
-        let center = Vec2::new(400.0, 400.0);
-        let offset = 200.0;
-        let position =
-            center + Vec2::new(elapsed_secs.cos() * offset, elapsed_secs.sin() * offset);
+    fn on_draw(&mut self, _helper: &mut WindowHelper, graphics: &mut Graphics2D) {
+        graphics.clear(Colour::WHITE);
+        renderer::render_shapes(&self.editor.shapes(), graphics);
+    }
 
-        graphics.draw_circle(position, 75.0, Color::from_rgb(0.8, 0.9, 1.0));
+    fn on_mouse_move(&mut self, helper: &mut WindowHelper, position: Vec2) {
+        self.mouse_position = Vector::new(position.x, position.y);
+        self.editor.move_to(self.mouse_position);
         helper.request_redraw();
     }
 
-    fn on_mouse_move(&mut self, _helper: &mut WindowHelper, position: Vec2) {
-        self.mouse_position = Vector::new(position.x, position.y)
+    fn on_mouse_button_down(&mut self, helper: &mut WindowHelper, button: MouseButton) {
+        match button {
+            MouseButton::Left => self.editor.click(self.mouse_position),
+            MouseButton::Right => {
+                self.editor.finish_path();
+                self.editor.select_at(self.mouse_position);
+            }
+            _ => return,
+        }
+        helper.request_redraw();
     }
 
-    fn on_mouse_button_down(&mut self, helper: &mut WindowHelper, _button: MouseButton) {
-        self.shapes.push(
-            Shape::Line(Segment::new(self.last_click, self.mouse_position),
-                        2.0, Color::RED));
-        self.last_click = self.mouse_position;
+    fn on_key_down(&mut self, helper: &mut WindowHelper, virtual_key_code: Option<VirtualKeyCode>,
+                   _scancode: KeyScancode) {
+        let ctrl = self.modifiers.ctrl() || self.modifiers.logo();
+        match virtual_key_code {
+            Some(VirtualKeyCode::Return | VirtualKeyCode::Escape) => self.editor.finish_path(),
+            Some(VirtualKeyCode::Delete | VirtualKeyCode::Backspace) => {
+                self.editor.delete_selected();
+            }
+            Some(VirtualKeyCode::Z) if ctrl && self.modifiers.shift() => {
+                self.editor.redo();
+            }
+            Some(VirtualKeyCode::Z) if ctrl => {
+                self.editor.undo();
+            }
+            Some(VirtualKeyCode::Y) if ctrl => {
+                self.editor.redo();
+            }
+            Some(VirtualKeyCode::S) if ctrl => self.save(),
+            _ => return,
+        }
         helper.request_redraw();
     }
-}
 
+    fn on_keyboard_modifiers_changed(&mut self, _helper: &mut WindowHelper, state: ModifiersState) {
+        self.modifiers = state;
+    }
+}
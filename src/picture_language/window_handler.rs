@@ -1,49 +1,194 @@
-use speedy2d::color::Color;
-use speedy2d::dimen::Vec2;
-use speedy2d::window::{WindowHandler, WindowHelper};
+use speedy2d::dimen::{UVec2, Vec2};
+use speedy2d::window::{MouseButton, MouseScrollDistance, VirtualKeyCode, KeyScancode, WindowCreationOptions,
+                       WindowHandler, WindowHelper, WindowPosition, WindowSize};
 use speedy2d::{Graphics2D, Window};
+use crate::picture_language::bounds::Bounds;
+use crate::picture_language::colour::Colour;
+use crate::picture_language::coordinates::CoordinateSystem;
+use crate::picture_language::renderer::{self, Renderer};
 use crate::picture_language::shape::Shape;
 use crate::picture_language::vector::Vector;
+use crate::picture_language::viewport::Viewport;
+
+// Pixels left around a picture that is fitted to the window
+const FIT_MARGIN: f32 = 20.0;
+// Each line of mouse wheel scrolling zooms by this much
+const ZOOM_STEP: f32 = 1.1;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ViewerOptions {
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+    pub fit: bool, // start with the picture scaled to fill the window, rather than in device pixels
+    pub coordinates: CoordinateSystem,
+}
+
+impl ViewerOptions {
+    pub fn new() -> Self {
+        ViewerOptions {
+            width: 800,
+            height: 800,
+            title: "SICP picture language".to_string(),
+            fit: false,
+            coordinates: CoordinateSystem::YDown,
+        }
+    }
+
+    pub fn with_size(self, width: u32, height: u32) -> Self {
+        ViewerOptions { width, height, ..self }
+    }
+
+    pub fn with_title(self, title: &str) -> Self {
+        ViewerOptions { title: title.to_string(), ..self }
+    }
+
+    pub fn with_fit(self, fit: bool) -> Self {
+        ViewerOptions { fit, ..self }
+    }
+
+    pub fn with_coordinates(self, coordinates: CoordinateSystem) -> Self {
+        ViewerOptions { coordinates, ..self }
+    }
+}
+
+impl Default for ViewerOptions {
+    fn default() -> Self {
+        ViewerOptions::new()
+    }
+}
 
 pub fn run_picture_window(shapes: Vec<Shape>) {
-    let window = Window::new_centered("Speedy2D: Animation", (800, 800)).unwrap();
-    window.run_loop(PictureWindowHandler {
-        shapes,
-    })
+    run_viewer(shapes, &ViewerOptions::new())
+}
+
+// The mouse wheel zooms about the pointer and dragging pans. F fits the picture to the window,
+// R or 0 goes back to the starting view, and + and - zoom about the centre.
+pub fn run_viewer(shapes: Vec<Shape>, options: &ViewerOptions) {
+    let size = WindowSize::PhysicalPixels(UVec2::new(options.width, options.height));
+    let window_options = WindowCreationOptions::new_windowed(size, Some(WindowPosition::Center))
+        .with_resizable(true);
+    let window = Window::new_with_options(&options.title, window_options).unwrap();
+    window.run_loop(PictureWindowHandler::new(shapes, options))
 }
 
 pub struct PictureWindowHandler {
     shapes: Vec<Shape>,
+    viewport: Viewport,
+    fit: bool,
+    fitted: bool, // refit when the window is resized, until the view is moved
+    mouse_position: Vector,
+    drag_start: Option<Vector>,
 }
 
-fn to_vec2(vec: &Vector) -> Vec2 {
-    Vec2 { x: vec.x, y: vec.y }
-}
+impl WindowHandler for PictureWindowHandler {
+    fn on_resize(&mut self, helper: &mut WindowHelper, size_pixels: UVec2) {
+        self.viewport.resize(size_pixels.x as f32, size_pixels.y as f32);
+        if self.fitted {
+            self.fit_to_window();
+        }
+        helper.request_redraw();
+    }
+
+    fn on_draw(&mut self, _helper: &mut WindowHelper, graphics: &mut Graphics2D) {
+        graphics.clear(Colour::WHITE);
+        let transform = self.viewport.transform();
+        let coordinates = self.viewport.coordinates();
+        let shapes: Vec<Shape> = self.shapes.iter()
+            .map(|shape| coordinates.transform_shape(shape, &transform))
+            .collect();
+        renderer::render_shapes(&shapes, graphics);
+    }
 
+    fn on_mouse_move(&mut self, helper: &mut WindowHelper, position: Vec2) {
+        self.mouse_position = Vector::new(position.x, position.y);
+        if let Some(start) = self.drag_start {
+            self.viewport.pan(self.mouse_position - start);
+            self.drag_start = Some(self.mouse_position);
+            self.fitted = false;
+            helper.request_redraw();
+        }
+    }
 
-impl WindowHandler for PictureWindowHandler {
-    fn on_draw(&mut self, helper: &mut WindowHelper, graphics: &mut Graphics2D) {
-        graphics.clear_screen(Color::WHITE);
-        for shape in &self.shapes {
-            match shape {
-                Shape::Line(ref segment, thickness, colour) =>
-                    graphics.draw_line(to_vec2(&segment.start), to_vec2(&segment.end),
-                                       *thickness, *colour),
-                Shape::PolyLine(lines, thickness, colour) => {
-                    lines.iter().for_each(|(from, to)|
-                        graphics.draw_line(to_vec2(from), to_vec2(to), *thickness, *colour));
-                }
-                Shape::Circle(centre, radius, colour) =>
-                    graphics.draw_circle(to_vec2(centre), *radius, *colour),
-            }
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.drag_start = Some(self.mouse_position);
+        }
+    }
+
+    fn on_mouse_button_up(&mut self, _helper: &mut WindowHelper, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.drag_start = None;
+        }
+    }
+
+    fn on_mouse_wheel_scroll(&mut self, helper: &mut WindowHelper, distance: MouseScrollDistance) {
+        let lines = match distance {
+            MouseScrollDistance::Lines { y, .. } => y as f32,
+            MouseScrollDistance::Pixels { y, .. } => y as f32 / 20.0,
+            MouseScrollDistance::Pages { y, .. } => y as f32 * 10.0,
+        };
+        self.viewport.zoom_at(self.mouse_position, ZOOM_STEP.powf(lines));
+        self.fitted = false;
+        helper.request_redraw();
+    }
+
+    fn on_key_down(&mut self, helper: &mut WindowHelper, virtual_key_code: Option<VirtualKeyCode>,
+                   _scancode: KeyScancode) {
+        match virtual_key_code {
+            Some(VirtualKeyCode::F) => self.fit_to_window(),
+            Some(VirtualKeyCode::R | VirtualKeyCode::Key0) => self.reset_view(),
+            Some(VirtualKeyCode::Equals) => self.zoom_at_centre(ZOOM_STEP),
+            Some(VirtualKeyCode::Minus) => self.zoom_at_centre(1.0 / ZOOM_STEP),
+            _ => return,
         }
         helper.request_redraw();
     }
 }
 
 impl PictureWindowHandler {
+    pub fn new(shapes: Vec<Shape>, options: &ViewerOptions) -> Self {
+        let mut handler = PictureWindowHandler {
+            shapes,
+            viewport: Viewport::new(options.width as f32, options.height as f32)
+                .with_coordinates(options.coordinates),
+            fit: options.fit,
+            fitted: false,
+            mouse_position: Vector::zero(),
+            drag_start: None,
+        };
+        handler.reset_view();
+        handler
+    }
+
     pub fn add(&mut self, shape: Shape) {
         self.shapes.push(shape);
     }
-}
 
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    // Everything in the picture, so nothing is left outside the window
+    pub fn fit_to_window(&mut self) {
+        match Bounds::union_all(self.shapes.iter().filter_map(Shape::bounds)) {
+            Some(bounds) => self.viewport.fit(&bounds, FIT_MARGIN),
+            None => self.viewport.reset(),
+        }
+        self.fitted = true;
+    }
+
+    pub fn reset_view(&mut self) {
+        if self.fit {
+            self.fit_to_window();
+        } else {
+            self.viewport.reset();
+            self.fitted = false;
+        }
+    }
+
+    fn zoom_at_centre(&mut self, factor: f32) {
+        self.viewport.zoom_at_centre(factor);
+        self.fitted = false;
+    }
+}